@@ -20,7 +20,7 @@ fn parse_input1(input: &[String]) -> Vec<i64> {
             .map(|c| c.to_digit(10).unwrap());
 
         let first = digits.next().unwrap();
-        let last = digits.last().unwrap_or(first);
+        let last = digits.next_back().unwrap_or(first);
 
         numbers.push(i64::from(first * 10 + last));
     }
@@ -1,140 +1,84 @@
-use std::{cell::RefCell, collections::HashSet};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
 
+use crate::generate::Rng;
+pub use crate::render::RenderStyle;
+
+/// # Panics
+///
+/// Panics if the maze is invalid, see [`Maze::parse`]
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
-    let maze = parse_input(input);
-    let mut visited: HashSet<Point2D> = HashSet::new();
-
-    visited.insert(maze.start);
-
-    let [start1, start2] = maze.get_from_start();
-
-    let mut cursor1 = Cursor {
-        point: maze.start.get_next(start1),
-        direction: start1,
-    };
-
-    let mut cursor2 = Cursor {
-        point: maze.start.get_next(start2),
-        direction: start2,
-    };
-
-    let mut step_count = 0;
-
-    while !visited.contains(&cursor1.point) || !visited.contains(&cursor2.point) {
-        visited.insert(cursor1.point);
-        visited.insert(cursor2.point);
-
-        cursor1 = maze.get_next(&cursor1);
-        cursor2 = maze.get_next(&cursor2);
-        step_count += 1;
-    }
-
-    step_count
+    parse_input(input).trace_loop().distance as i64
 }
 
+/// # Panics
+///
+/// Panics if the maze is invalid, see [`Maze::parse`]
 #[must_use]
 pub fn part2(input: &[String]) -> i64 {
     let mut maze = parse_input(input);
-    let mut visited: HashSet<Point2D> = HashSet::new();
-
-    visited.insert(maze.start);
-
-    let [start, _] = maze.get_from_start();
-
-    let mut cursor = Cursor {
-        point: maze.start.get_next(start),
-        direction: start,
-    };
-
-    while cursor.point != maze.start {
-        visited.insert(cursor.point);
-
-        cursor = maze.get_next(&cursor);
-    }
-
-    let maze_y = maze.tiles.len();
-    let maze_x = maze.tiles[0].len();
-
-    // TODO: Inside loop function is not correct
-    let outside_loop = |point: Point2D| {
-        let north_bound = || {
-            for y in 0..=point.y {
-                let new_point = Point2D { x: point.x, y };
-
-                if visited.contains(&new_point) {
-                    return false;
-                }
-            }
-
-            true
-        };
-
-        let south_bound = || {
-            for y in point.y..maze_y {
-                let new_point = Point2D { x: point.x, y };
+    let visited = maze.trace_loop().points();
 
-                if visited.contains(&new_point) {
-                    return false;
-                }
-            }
-
-            true
-        };
+    maze.clear_junk(&visited);
 
-        let east_bound = || {
-            for x in 0..=point.x {
-                let new_point = Point2D { x, y: point.y };
-
-                if visited.contains(&new_point) {
-                    return false;
-                }
-            }
-
-            true
-        };
-
-        let west_bound = || {
-            for x in point.x..maze_x {
-                let new_point = Point2D { x, y: point.y };
-
-                if visited.contains(&new_point) {
-                    return false;
-                }
-            }
+    maze.tiles.iter().map(|row| count_inside(row)).sum()
+}
 
-            true
-        };
+/// Redraws the maze, separating the main loop from the tiles inside and outside of it
+///
+/// * [`RenderStyle::Plain`] keeps the loop's pipe characters and replaces every other tile with
+///   `I` (inside the loop) or `O` (outside)
+/// * [`RenderStyle::Ansi`] keeps every character and colors tiles off the loop green when
+///   inside and red when outside
+/// * [`RenderStyle::Unicode`] draws the loop with box-drawing characters, shades the inside and
+///   blanks the outside
+///
+/// # Panics
+///
+/// Panics if the maze is invalid, see [`Maze::parse`]
+#[must_use]
+pub fn render(input: &[String], style: RenderStyle) -> String {
+    let maze = parse_input(input);
+    let visited = maze.trace_loop().points();
 
-        north_bound() || south_bound() || east_bound() || west_bound()
-    };
+    maze.render(&visited, style)
+}
 
-    //maze.fancy_print(&visited);
+/// Draws the maze as an SVG image
+///
+/// Pipes are drawn as line segments, with the main loop highlighted and `S` marked by a dot.
+/// Tiles enclosed by the loop are filled and tiles outside of it are shaded.
+///
+/// # Panics
+///
+/// Panics if the maze is invalid, see [`Maze::parse`]
+#[must_use]
+pub fn to_svg(input: &[String]) -> String {
+    let maze = parse_input(input);
+    let visited = maze.trace_loop().points();
 
-    for y in 0..maze.tiles.len() {
-        for x in 0..maze.tiles[0].len() {
-            let point = Point2D { x, y };
-            if !visited.contains(&point) {
-                maze.tiles[y][x] = Tile::Empty;
-            }
-        }
-    }
+    maze.to_svg(&visited)
+}
 
-    for y in 0..maze.tiles.len() {
-        for x in 0..maze.tiles[0].len() {
-            let point = Point2D { x, y };
-            if maze.get_tile_at(point) == Tile::Empty && outside_loop(point) {
-                maze.poison_tile(point);
-            }
+/// Counts the tiles of a row that lie inside the loop, once everything off the loop is `Empty`
+///
+/// Scanning from the left edge, every loop tile with a northern connection (`|`, `L` and `J`)
+/// crosses the boundary once, so an odd number of crossings means the tile is inside.
+fn count_inside(row: &[Tile]) -> i64 {
+    let mut inside = false;
+    let mut count = 0;
+
+    for &tile in row {
+        match tile {
+            Tile::Vertical | Tile::NorthEast | Tile::NorthWest => inside = !inside,
+            Tile::Empty if inside => count += 1,
+            _ => {}
         }
     }
 
-    maze.fancy_print(&visited);
-
-    maze.tiles
-        .iter()
-        .map(|row| row.iter().filter(|&tile| *tile == Tile::Empty).count() as i64)
-        .sum()
+    count
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -151,6 +95,15 @@ enum Tile {
 }
 
 impl Tile {
+    const PIPES: [Self; 6] = [
+        Self::Vertical,
+        Self::Horizontal,
+        Self::NorthEast,
+        Self::NorthWest,
+        Self::SouthEast,
+        Self::SouthWest,
+    ];
+
     const fn from_char(ch: char) -> Self {
         match ch {
             'S' => Self::Start,
@@ -226,45 +179,45 @@ impl Tile {
         }
     }
 
-    fn is_north(self) -> bool {
-        self == Self::NorthEast || self == Self::NorthWest
-    }
-
-    fn is_south(self) -> bool {
-        self == Self::SouthEast || self == Self::SouthWest
-    }
-
-    fn is_east(self) -> bool {
-        self == Self::NorthEast || self == Self::SouthEast
-    }
-
-    fn is_west(self) -> bool {
-        self == Self::NorthWest || self == Self::SouthWest
-    }
-
-    fn horizontal_gap(top: Self, bottom: Self) -> bool {
-        (top.is_north() || top == Self::Horizontal || top == Self::Empty)
-            && (bottom.is_south() || bottom == Self::Horizontal || bottom == Self::Empty)
+    /// The two directions a pipe can be left through
+    fn exits(self) -> [Direction; 2] {
+        match self {
+            Self::Vertical => [Direction::North, Direction::South],
+            Self::Horizontal => [Direction::West, Direction::East],
+            Self::NorthEast => [Direction::North, Direction::East],
+            Self::NorthWest => [Direction::North, Direction::West],
+            Self::SouthEast => [Direction::South, Direction::East],
+            Self::SouthWest => [Direction::South, Direction::West],
+            _ => unreachable!(),
+        }
     }
 
-    fn vertical_gap(left: Self, right: Self) -> bool {
-        (left.is_west() || left == Self::Vertical || left == Self::Empty)
-            && (right.is_east() || right == Self::Vertical || right == Self::Empty)
+    fn to_box_char(self) -> char {
+        match self {
+            Tile::Vertical => '│',
+            Tile::Horizontal => '─',
+            Tile::NorthEast => '└',
+            Tile::NorthWest => '┘',
+            Tile::SouthEast => '┌',
+            Tile::SouthWest => '┐',
+            _ => self.to_char(),
+        }
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Direction {
+pub enum Direction {
     North,
     South,
     East,
     West,
 }
 
+/// A tile position, with `y` growing southwards
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
-struct Point2D {
-    x: usize,
-    y: usize,
+pub struct Point2D {
+    pub x: usize,
+    pub y: usize,
 }
 
 impl Point2D {
@@ -295,13 +248,159 @@ struct Cursor {
     direction: Direction,
 }
 
+/// Which way the main loop turns when walked in the order of [`MainLoop::steps`], as seen on
+/// screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// The main loop of a maze, walked once starting from `S`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MainLoop {
+    /// Every loop tile in walking order, starting at `S`, with the direction taken to leave it
+    pub steps: Vec<(Point2D, Direction)>,
+    /// The tile that takes the most steps to reach from `S` in either direction
+    pub farthest: Point2D,
+    /// Number of steps from `S` to [`Self::farthest`]
+    pub distance: usize,
+    pub winding: Winding,
+}
+
+impl MainLoop {
+    /// Number of tiles in the loop
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    #[must_use]
+    pub fn points(&self) -> HashSet<Point2D> {
+        self.steps.iter().map(|&(point, _)| point).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MazeError {
+    NoStart,
+    /// The number of `S` tiles found
+    MultipleStarts(usize),
+    /// No pipe shape at `S` closes a loop
+    NoLoop,
+    /// The number of pipe shapes at `S` that close a loop
+    AmbiguousStart(usize),
+}
+
+impl Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoStart => write!(f, "the maze has no start tile"),
+            Self::MultipleStarts(count) => write!(f, "the maze has {count} start tiles"),
+            Self::NoLoop => write!(f, "no pipe at the start tile closes a loop"),
+            Self::AmbiguousStart(count) => {
+                write!(f, "{count} different pipes at the start tile close a loop")
+            }
+        }
+    }
+}
+
+impl Error for MazeError {}
+
 #[derive(Debug, PartialEq, Eq)]
-struct Maze {
+pub struct Maze {
     tiles: Vec<Vec<Tile>>,
     start: Point2D,
 }
 
 impl Maze {
+    /// Parses a maze and replaces `S` with the pipe that closes the main loop
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is not exactly one `S`, or if not exactly
+    /// one pipe shape at `S` closes a loop through it
+    pub fn parse(input: &[String]) -> Result<Self, MazeError> {
+        let mut start = None;
+        let mut start_count = 0;
+
+        let tiles = input
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, ch)| {
+                        if ch == 'S' {
+                            start = Some(Point2D { x, y });
+                            start_count += 1;
+                        }
+
+                        Tile::from_char(ch)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let start = match (start, start_count) {
+            (Some(start), 1) => start,
+            (None, _) => return Err(MazeError::NoStart),
+            (Some(_), _) => return Err(MazeError::MultipleStarts(start_count)),
+        };
+
+        let mut maze = Self { tiles, start };
+        maze.init_start()?;
+
+        Ok(maze)
+    }
+
+    /// Walks the main loop once from `S`
+    ///
+    /// The winding comes from the sign of the loop's shoelace area.
+    #[must_use]
+    pub fn trace_loop(&self) -> MainLoop {
+        let start = self.get_tile_at(self.start).exits()[0];
+        let mut steps = vec![(self.start, start)];
+
+        let mut cursor = Cursor {
+            point: self.start.get_next(start),
+            direction: start,
+        };
+
+        while cursor.point != self.start {
+            let next = self.get_next(&cursor);
+            steps.push((cursor.point, next.direction));
+            cursor = next;
+        }
+
+        // Coordinates are bounded by the input's size, so they fit in an i64
+        #[allow(clippy::cast_possible_wrap)]
+        let twice_area: i64 = steps
+            .iter()
+            .zip(steps.iter().cycle().skip(1))
+            .map(|(&(a, _), &(b, _))| (a.x as i64) * (b.y as i64) - (b.x as i64) * (a.y as i64))
+            .sum();
+
+        // A closed walk on a grid always has an even length
+        let distance = steps.len() / 2;
+
+        MainLoop {
+            farthest: steps[distance].0,
+            distance,
+            winding: if twice_area > 0 {
+                Winding::Clockwise
+            } else {
+                Winding::CounterClockwise
+            },
+            steps,
+        }
+    }
+
     fn get_next(&self, cursor: &Cursor) -> Cursor {
         let tile = self.tiles[cursor.point.y][cursor.point.x];
 
@@ -313,64 +412,78 @@ impl Maze {
         }
     }
 
-    fn init_start(&mut self) {
-        let [dir1, dir2] = self.get_from_start();
+    /// Replaces `S` with the only pipe shape that closes a loop through it
+    fn init_start(&mut self) -> Result<(), MazeError> {
+        let Point2D { x, y } = self.start;
+        let mut found = None;
+        let mut count = 0;
 
-        self.tiles[self.start.y][self.start.x] = match dir1 {
-            Direction::North => match dir2 {
-                Direction::North | Direction::South => Tile::Vertical,
-                Direction::East => Tile::NorthEast,
-                Direction::West => Tile::NorthWest,
-            },
-            Direction::South => match dir2 {
-                Direction::North | Direction::South => Tile::Vertical,
-                Direction::East => Tile::SouthEast,
-                Direction::West => Tile::SouthWest,
-            },
-            Direction::East => match dir2 {
-                Direction::North => Tile::NorthEast,
-                Direction::South => Tile::SouthEast,
-                Direction::East | Direction::West => Tile::Horizontal,
-            },
-            Direction::West => match dir2 {
-                Direction::North => Tile::NorthWest,
-                Direction::South => Tile::SouthWest,
-                Direction::East | Direction::West => Tile::Horizontal,
-            },
-        };
-    }
+        for shape in Tile::PIPES {
+            self.tiles[y][x] = shape;
 
-    fn get_from_start(&self) -> [Direction; 2] {
-        let mut directions = [Direction::North, Direction::North];
-        let mut index = 0;
-
-        if self.start.y != 0
-            && self.tiles[self.start.y - 1][self.start.x].connects(Direction::North)
-        {
-            directions[index] = Direction::North;
-            index += 1;
+            if self.closes_loop() {
+                found = Some(shape);
+                count += 1;
+            }
         }
 
-        if self.start.y != self.tiles.len() - 1
-            && self.tiles[self.start.y + 1][self.start.x].connects(Direction::South)
-        {
-            directions[index] = Direction::South;
-            index += 1;
+        match (found, count) {
+            (Some(shape), 1) => {
+                self.tiles[y][x] = shape;
+                Ok(())
+            }
+            (None, _) => Err(MazeError::NoLoop),
+            (Some(_), _) => Err(MazeError::AmbiguousStart(count)),
         }
+    }
 
-        if self.start.x != 0 && self.tiles[self.start.y][self.start.x - 1].connects(Direction::West)
-        {
-            directions[index] = Direction::West;
-            index += 1;
-        }
+    /// Checks that leaving the start tile through its first exit leads back into it through
+    /// the second one
+    fn closes_loop(&self) -> bool {
+        let mut cursor = Cursor {
+            point: self.start,
+            direction: self.get_tile_at(self.start).exits()[0],
+        };
+
+        // Every tile can be visited at most once before the walk returns to the start
+        for _ in 0..self.tiles.len() * self.tiles[0].len() {
+            let Some(point) = self.checked_next(cursor.point, cursor.direction) else {
+                return false;
+            };
+
+            let tile = self.get_tile_at(point);
+
+            if !tile.connects(cursor.direction) {
+                return false;
+            }
 
-        if self.start.x != self.tiles[0].len() - 1
-            && self.tiles[self.start.y][self.start.x + 1].connects(Direction::East)
-        {
-            directions[index] = Direction::East;
+            if point == self.start {
+                return true;
+            }
+
+            cursor = Cursor {
+                point,
+                direction: tile.rotate(cursor.direction),
+            };
         }
 
-        directions
+        false
+    }
+
+    fn checked_next(&self, point: Point2D, direction: Direction) -> Option<Point2D> {
+        let next = match direction {
+            Direction::North => Point2D {
+                x: point.x,
+                y: point.y.checked_sub(1)?,
+            },
+            Direction::West => Point2D {
+                x: point.x.checked_sub(1)?,
+                y: point.y,
+            },
+            _ => point.get_next(direction),
+        };
+
+        (next.y < self.tiles.len() && next.x < self.tiles[next.y].len()).then_some(next)
     }
 
     fn get_tile_at(&self, point: Point2D) -> Tile {
@@ -381,172 +494,387 @@ impl Maze {
         }
     }
 
-    fn get_next_tile(&self, cursor: &Cursor) -> Tile {
-        let is_edge = match cursor.direction {
-            Direction::North => cursor.point.y == 0,
-            Direction::South => cursor.point.y == self.tiles.len() - 1,
-            Direction::East => cursor.point.x == self.tiles[0].len() - 1,
-            Direction::West => cursor.point.x == 0,
-        };
-
-        if is_edge {
-            Tile::External
-        } else {
-            self.get_tile_at(cursor.point.get_next(cursor.direction))
-        }
-    }
-
-    fn poison_tile_dir(&mut self, point: Point2D, direction: Direction) {
-        let shared = RefCell::new(self);
-
-        let is_gap = |tile: Tile, cursor: &Cursor| match cursor.direction {
-            Direction::North | Direction::South => Tile::vertical_gap(
-                tile,
-                shared
-                    .borrow()
-                    .get_tile_at(cursor.point.get_next(Direction::East)),
-            ),
-            Direction::East | Direction::West => Tile::horizontal_gap(
-                tile,
-                shared
-                    .borrow()
-                    .get_tile_at(cursor.point.get_next(Direction::South)),
-            ),
-        };
+    /// Marks every `Empty` tile that can be reached from outside the maze as `External`
+    ///
+    /// The fill runs over the maze at double resolution: tiles sit at odd coordinates and the
+    /// cells between two tiles are only walls when both pipes connect through them, so the
+    /// fill can squeeze between pipes that merely touch. It uses an explicit stack, so large
+    /// mazes can not overflow the call stack.
+    fn flood_outside(&mut self) {
+        let height = self.tiles.len() * 2 + 1;
+        let width = self.tiles.first().map_or(0, Vec::len) * 2 + 1;
+        let mut closed = vec![false; width * height];
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile == Tile::Empty || tile == Tile::External {
+                    continue;
+                }
 
-        let mut cursor = Cursor { point, direction };
+                let point = Point2D { x, y };
+                let (cx, cy) = (x * 2 + 1, y * 2 + 1);
 
-        if shared.borrow().get_tile_at(point) == Tile::Empty {
-            shared.borrow_mut().tiles[point.y][point.x] = Tile::External;
-        }
+                closed[cy * width + cx] = true;
 
-        let mut next_tile = shared.borrow().get_next_tile(&cursor);
+                if tile.connects(Direction::West)
+                    && self
+                        .get_tile_at(point.get_next(Direction::East))
+                        .connects(Direction::East)
+                {
+                    closed[cy * width + cx + 1] = true;
+                }
 
-        if next_tile == Tile::External {
-            return;
+                if tile.connects(Direction::North)
+                    && self
+                        .get_tile_at(point.get_next(Direction::South))
+                        .connects(Direction::South)
+                {
+                    closed[(cy + 1) * width + cx] = true;
+                }
+            }
         }
 
-        cursor.point = cursor.point.get_next(cursor.direction);
+        // The border of the doubled grid never holds a tile, so one corner reaches all of it
+        let mut stack = vec![(0, 0)];
+        closed[0] = true;
 
-        while next_tile == Tile::Empty || is_gap(next_tile, &cursor) {
-            for direction in [
-                Direction::North,
-                Direction::East,
-                Direction::South,
-                Direction::West,
-            ] {
-                // TODO: Remove recursion to avoid stack overflow
-                shared.borrow_mut().poison_tile_dir(cursor.point, direction);
+        while let Some((x, y)) = stack.pop() {
+            if x % 2 == 1 && y % 2 == 1 {
+                self.tiles[y / 2][x / 2] = Tile::External;
             }
 
-            next_tile = shared.borrow().get_next_tile(&cursor);
+            let neighbours = [
+                (y > 0).then(|| (x, y - 1)),
+                (y + 1 < height).then(|| (x, y + 1)),
+                (x > 0).then(|| (x - 1, y)),
+                (x + 1 < width).then(|| (x + 1, y)),
+            ];
+
+            for (nx, ny) in neighbours.into_iter().flatten() {
+                if !closed[ny * width + nx] {
+                    closed[ny * width + nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+    }
 
-            if next_tile == Tile::External {
-                break;
+    /// Replaces every tile that is not part of the loop with `Empty`
+    fn clear_junk(&mut self, visited: &HashSet<Point2D>) {
+        for y in 0..self.tiles.len() {
+            for x in 0..self.tiles[0].len() {
+                let point = Point2D { x, y };
+                if !visited.contains(&point) {
+                    self.tiles[y][x] = Tile::Empty;
+                }
             }
-            cursor.point = cursor.point.get_next(cursor.direction);
         }
     }
 
-    fn poison_tile(&mut self, point: Point2D) {
-        for direction in [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ] {
-            self.poison_tile_dir(point, direction);
+    /// Copies the maze with only the loop left and every tile outside of it marked `External`
+    fn classify(&self, visited: &HashSet<Point2D>) -> Maze {
+        let mut cleared = Maze {
+            tiles: self.tiles.clone(),
+            start: self.start,
+        };
+
+        cleared.clear_junk(visited);
+        cleared.flood_outside();
+
+        cleared
+    }
+
+    fn render(&self, visited: &HashSet<Point2D>, style: RenderStyle) -> String {
+        let cleared = self.classify(visited);
+        let mut output = String::new();
+
+        for (row, cleared_row) in self.tiles.iter().zip(&cleared.tiles) {
+            for (&tile, &cleared_tile) in row.iter().zip(cleared_row) {
+                let on_loop = cleared_tile != Tile::Empty && cleared_tile != Tile::External;
+                let outside = cleared_tile == Tile::External;
+
+                match style {
+                    RenderStyle::Plain if on_loop => output.push(tile.to_char()),
+                    RenderStyle::Plain => output.push(if outside { 'O' } else { 'I' }),
+                    RenderStyle::Ansi if on_loop => output.push(tile.to_char()),
+                    RenderStyle::Ansi => {
+                        let color = if outside { 91 } else { 92 };
+                        let _ = write!(output, "\x1b[{color}m{}\x1b[0m", tile.to_char());
+                    }
+                    RenderStyle::Unicode if on_loop => output.push(tile.to_box_char()),
+                    RenderStyle::Unicode => output.push(if outside { ' ' } else { '░' }),
+                }
+            }
+
+            output.push('\n');
         }
+
+        output
     }
 
-    fn fancy_print(&self, visited: &HashSet<Point2D>) {
-        for y in 0..self.tiles.len() {
-            for x in 0..self.tiles[y].len() {
-                let point = Point2D { x, y };
-                if visited.contains(&point) {
-                    print!("{}", self.get_tile_at(point).to_char());
+    fn to_svg(&self, visited: &HashSet<Point2D>) -> String {
+        const TILE: usize = 10;
+        const HALF: usize = TILE / 2;
+
+        let cleared = self.classify(visited);
+        let height = self.tiles.len() * TILE;
+        let width = self.tiles.first().map_or(0, Vec::len) * TILE;
+
+        let mut output = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+             width=\"{width}\" height=\"{height}\">\n"
+        );
+
+        let mut loop_path = String::new();
+        let mut junk_path = String::new();
+
+        for (y, (row, cleared_row)) in self.tiles.iter().zip(&cleared.tiles).enumerate() {
+            for (x, (&tile, &cleared_tile)) in row.iter().zip(cleared_row).enumerate() {
+                let fill = match cleared_tile {
+                    Tile::External => Some("#d8d8d8"),
+                    Tile::Empty => Some("#8fd18f"),
+                    _ => None,
+                };
+
+                if let Some(fill) = fill {
+                    let _ = writeln!(
+                        output,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{TILE}\" height=\"{TILE}\" \
+                         fill=\"{fill}\"/>",
+                        x * TILE,
+                        y * TILE
+                    );
+                }
+
+                if !Tile::PIPES.contains(&tile) {
+                    continue;
+                }
+
+                let path = if visited.contains(&Point2D { x, y }) {
+                    &mut loop_path
                 } else {
-                    print!("\x1b[91m{}\x1b[0m", self.get_tile_at(point).to_char());
+                    &mut junk_path
+                };
+
+                let (cx, cy) = (x * TILE + HALF, y * TILE + HALF);
+
+                for direction in tile.exits() {
+                    let (ex, ey) = match direction {
+                        Direction::North => (cx, cy - HALF),
+                        Direction::South => (cx, cy + HALF),
+                        Direction::East => (cx + HALF, cy),
+                        Direction::West => (cx - HALF, cy),
+                    };
+
+                    let _ = write!(path, "M{cx} {cy}L{ex} {ey}");
                 }
             }
-
-            println!();
         }
 
-        println!();
+        let _ = writeln!(
+            output,
+            "<path d=\"{junk_path}\" stroke=\"#8c8c8c\" stroke-width=\"1\" fill=\"none\"/>"
+        );
+        let _ = writeln!(
+            output,
+            "<path d=\"{loop_path}\" stroke=\"#d62728\" stroke-width=\"3\" \
+             stroke-linecap=\"round\" fill=\"none\"/>"
+        );
+        let _ = writeln!(
+            output,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#1f77b4\"/>",
+            self.start.x * TILE + HALF,
+            self.start.y * TILE + HALF,
+            HALF - 1
+        );
+
+        output.push_str("</svg>\n");
+        output
     }
 }
 
-struct PoisonIter {
-    stack: Vec<(Cursor, u32)>,
-    max_depth: u32,
+/// A maze made by [`generate`], along with the answers it was built to have
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedMaze {
+    pub input: Vec<String>,
+    /// The answer to part 1
+    pub farthest: i64,
+    /// The answer to part 2
+    pub enclosed: i64,
 }
 
-impl PoisonIter {
-    fn new(root: Cursor, max_depth: u32) -> Self {
-        Self {
-            stack: vec![(root, 0)],
-            max_depth,
+/// Generates a random `width` by `height` maze from `seed`
+///
+/// The main loop is the outline of a random polyomino grown one square at a time, drawn at
+/// twice its scale so that the loop can enclose tiles. A square is only added if it keeps the
+/// shape free of holes and of squares touching only at a corner, so the outline stays a single
+/// loop. Every other tile gets a random pipe or is left empty, apart from the neighbours of
+/// `S` which are always empty so that its shape is unambiguous.
+///
+/// Each square covers 4 tiles, so Pick's theorem gives the enclosed tiles from the number of
+/// squares and the loop length.
+///
+/// # Panics
+///
+/// Panics if `width` or `height` is less than 5
+#[must_use]
+pub fn generate(seed: u64, width: usize, height: usize) -> GeneratedMaze {
+    assert!(width >= 5 && height >= 5, "mazes must be at least 5x5");
+
+    let mut rng = Rng::new(seed);
+    let (cols, rows) = ((width - 3) / 2, (height - 3) / 2);
+    let region = grow_region(&mut rng, cols, rows);
+    let in_region = |col: usize, row: usize| {
+        col > 0 && row > 0 && col <= cols && row <= rows && region[(row - 1) * cols + col - 1]
+    };
+
+    let mut tiles = vec![vec!['.'; width]; height];
+    let mut on_loop = Vec::new();
+
+    // Corner (a, b) of the squares sits on tile (2a + 1, 2b + 1), and the squares around it
+    // are shifted by one so that `in_region` can look at the ones left of and above the edge
+    for b in 0..=rows {
+        for a in 0..=cols {
+            let (north_west, north_east) = (in_region(a, b), in_region(a + 1, b));
+            let (south_west, south_east) = (in_region(a, b + 1), in_region(a + 1, b + 1));
+
+            let north = north_west != north_east;
+            let south = south_west != south_east;
+            let west = north_west != south_west;
+            let east = north_east != south_east;
+
+            let (x, y) = (2 * a + 1, 2 * b + 1);
+
+            let pipe = match (north, south, west, east) {
+                (true, true, false, false) => '|',
+                (false, false, true, true) => '-',
+                (true, false, false, true) => 'L',
+                (true, false, true, false) => 'J',
+                (false, true, false, true) => 'F',
+                (false, true, true, false) => '7',
+                _ => continue,
+            };
+
+            tiles[y][x] = pipe;
+            on_loop.push((x, y));
+
+            if east {
+                tiles[y][x + 1] = '-';
+                on_loop.push((x + 1, y));
+            }
+
+            if south {
+                tiles[y + 1][x] = '|';
+                on_loop.push((x, y + 1));
+            }
         }
     }
-}
 
-impl Iterator for PoisonIter {
-    type Item = Point2D;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let (state, depth) = self.stack.pop()?;
-
-        if depth < self.max_depth {
-            for direction in [
-                Direction::North,
-                Direction::East,
-                Direction::South,
-                Direction::West,
-            ] {
-                self.stack.push((
-                    Cursor {
-                        point: state.point.get_next(direction),
-                        direction,
-                    },
-                    depth + 1,
-                ));
-            }
+    let squares = i64::try_from(region.iter().filter(|&&square| square).count()).unwrap();
+    let loop_length = i64::try_from(on_loop.len()).unwrap();
+
+    let loop_tiles = tiles.clone();
+
+    for tile in tiles.iter_mut().flatten() {
+        if *tile == '.' && rng.below(3) == 0 {
+            *tile = ['|', '-', 'L', 'J', '7', 'F'][rng.below(6)];
+        }
+    }
+
+    let (start_x, start_y) = on_loop[rng.below(on_loop.len())];
+    tiles[start_y][start_x] = 'S';
+
+    for (x, y) in [
+        (start_x, start_y - 1),
+        (start_x, start_y + 1),
+        (start_x - 1, start_y),
+        (start_x + 1, start_y),
+    ] {
+        if loop_tiles[y][x] == '.' {
+            tiles[y][x] = '.';
         }
+    }
 
-        Some(state.point)
+    GeneratedMaze {
+        input: tiles.into_iter().map(String::from_iter).collect(),
+        farthest: loop_length / 2,
+        enclosed: 4 * squares - loop_length / 2 + 1,
     }
 }
 
-fn parse_input(input: &[String]) -> Maze {
-    let mut start = Point2D::default();
-
-    let tiles = input
-        .iter()
-        .enumerate()
-        .map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(x, ch)| {
-                    if ch == 'S' {
-                        start = Point2D { x, y };
-                    }
+/// Grows a random polyomino without holes or corner-only contacts on a `cols` by `rows` grid
+fn grow_region(rng: &mut Rng, cols: usize, rows: usize) -> Vec<bool> {
+    let mut region = vec![false; cols * rows];
+    let mut squares = vec![(rng.below(cols), rng.below(rows))];
+    let target = 1 + rng.below(cols * rows);
+
+    region[squares[0].1 * cols + squares[0].0] = true;
+
+    for _ in 0..target * 20 {
+        if squares.len() == target {
+            break;
+        }
+
+        let (col, row) = squares[rng.below(squares.len())];
+        let (col, row) = match rng.below(4) {
+            0 if row > 0 => (col, row - 1),
+            1 if row + 1 < rows => (col, row + 1),
+            2 if col > 0 => (col - 1, row),
+            3 if col + 1 < cols => (col + 1, row),
+            _ => continue,
+        };
+
+        if !region[row * cols + col] && can_grow(&region, cols, rows, col, row) {
+            region[row * cols + col] = true;
+            squares.push((col, row));
+        }
+    }
 
-                    Tile::from_char(ch)
-                })
-                .collect()
-        })
-        .collect();
+    region
+}
 
-    let mut maze = Maze { tiles, start };
-    maze.init_start();
+/// Checks that adding the square keeps the region a single loop outline
+///
+/// The region squares among its 8 neighbours must form one unbroken run, otherwise the new
+/// square would close off a hole, and no diagonal neighbour may be in the region without one
+/// of the two squares between them.
+fn can_grow(region: &[bool], cols: usize, rows: usize, col: usize, row: usize) -> bool {
+    // Clockwise from north, so even indices are edge neighbours and odd ones are diagonals
+    let ring: Vec<bool> = [
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+        (-1, -1),
+    ]
+    .iter()
+    .map(
+        |&(dx, dy)| match (col.checked_add_signed(dx), row.checked_add_signed(dy)) {
+            (Some(x), Some(y)) => x < cols && y < rows && region[y * cols + x],
+            _ => false,
+        },
+    )
+    .collect();
+
+    let runs = (0..8).filter(|&i| !ring[i] && ring[(i + 1) % 8]).count();
+    let corner_only = (1..8)
+        .step_by(2)
+        .any(|i| ring[i] && !ring[i - 1] && !ring[(i + 1) % 8]);
+
+    runs == 1 && !corner_only
+}
 
-    maze
+fn parse_input(input: &[String]) -> Maze {
+    Maze::parse(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::{HashMap, VecDeque};
 
     fn get_test_input_a() -> [String; 5] {
         [
@@ -640,11 +968,33 @@ mod tests {
     }
 
     fn get_test_input_d() -> [String; 10] {
-        todo!()
+        [
+            ".F----7F7F7F7F-7....".to_owned(),
+            ".|F--7||||||||FJ....".to_owned(),
+            ".||.FJ||||||||L7....".to_owned(),
+            "FJL7L7LJLJ||LJ.L-7..".to_owned(),
+            "L--J.L7...LJS7F-7L7.".to_owned(),
+            "....F-J..F7FJ|L7L7L7".to_owned(),
+            "....L7.F7||L7|.L7L7|".to_owned(),
+            ".....|FJLJ|FJ|F7|.LJ".to_owned(),
+            "....FJL-7.||.||||...".to_owned(),
+            "....L---J.LJ.LJLJ...".to_owned(),
+        ]
     }
 
     fn get_test_input_e() -> [String; 10] {
-        todo!()
+        [
+            "FF7FSF7F7F7F7F7F---7".to_owned(),
+            "L|LJ||||||||||||F--J".to_owned(),
+            "FL-7LJLJ||||||LJL-77".to_owned(),
+            "F--JF--7||LJLJ7F7FJ-".to_owned(),
+            "L---JF-JLJ.||-FJLJJ7".to_owned(),
+            "|F|F-JF---7F7-L7L|7|".to_owned(),
+            "|FFJF7L7F-JF7|JL---7".to_owned(),
+            "7-L-JL7||F7|L7F-7F7|".to_owned(),
+            "L.L7LFJ|||||FJL7||LJ".to_owned(),
+            "L7JLJL-JLJLJL--JLJ.L".to_owned(),
+        ]
     }
 
     #[test]
@@ -656,9 +1006,88 @@ mod tests {
     }
 
     #[test]
-    fn poison_tile_test() {
+    fn parse_start_test() {
+        // The `-` west of `S` also connects to it, but is not part of the loop
+        let mut input = get_test_input_a();
+        input[1] = "-S-7.".to_owned();
+
+        let maze = Maze::parse(&input).unwrap();
+
+        assert_eq!(maze.tiles[1][1], Tile::SouthEast);
+        assert_eq!(part2(&input), 1);
+
+        let figure_eight = [
+            "F-7..".to_owned(),
+            "|.|..".to_owned(),
+            "L-S-7".to_owned(),
+            "..|.|".to_owned(),
+            "..L-J".to_owned(),
+        ];
+
+        assert_eq!(
+            Maze::parse(&figure_eight),
+            Err(MazeError::AmbiguousStart(2))
+        );
+
+        let mut broken = get_test_input_a();
+        broken[3] = ".L-..".to_owned();
+
+        assert_eq!(Maze::parse(&broken), Err(MazeError::NoLoop));
+
+        let mut edge = get_test_input_a();
+        edge[1] = "S--7.".to_owned();
+
+        assert_eq!(Maze::parse(&edge), Err(MazeError::NoLoop));
+        assert_eq!(Maze::parse(&["....".to_owned()]), Err(MazeError::NoStart));
+        assert_eq!(
+            Maze::parse(&[".S.S".to_owned()]),
+            Err(MazeError::MultipleStarts(2))
+        );
+    }
+
+    #[test]
+    fn trace_loop_test() {
+        let main_loop = parse_input(&get_test_input_a()).trace_loop();
+
+        assert_eq!(main_loop.len(), 8);
+        assert_eq!(
+            main_loop.steps[0],
+            (Point2D { x: 1, y: 1 }, Direction::South)
+        );
+        assert_eq!(
+            main_loop.steps[2],
+            (Point2D { x: 1, y: 3 }, Direction::East)
+        );
+        assert_eq!(
+            main_loop.steps[7],
+            (Point2D { x: 2, y: 1 }, Direction::West)
+        );
+        assert_eq!(main_loop.farthest, Point2D { x: 3, y: 3 });
+        assert_eq!(main_loop.distance, 4);
+        assert_eq!(main_loop.winding, Winding::CounterClockwise);
+
+        let main_loop = parse_input(&get_test_input_b()).trace_loop();
+
+        assert_eq!(main_loop.len(), 16);
+        assert_eq!(main_loop.farthest, Point2D { x: 4, y: 2 });
+        assert_eq!(main_loop.winding, Winding::CounterClockwise);
+
+        let mut input = get_test_input_a();
+        input[1] = ".F-S.".to_owned();
+
+        let main_loop = parse_input(&input).trace_loop();
+
+        assert_eq!(
+            main_loop.steps[1],
+            (Point2D { x: 3, y: 2 }, Direction::South)
+        );
+        assert_eq!(main_loop.winding, Winding::Clockwise);
+    }
+
+    #[test]
+    fn flood_outside_test() {
         let mut maze = parse_input(&get_test_input_a());
-        maze.poison_tile(Point2D { x: 0, y: 0 });
+        maze.flood_outside();
 
         for tile in &maze.tiles[0] {
             assert_eq!(*tile, Tile::External);
@@ -675,6 +1104,185 @@ mod tests {
         for tile in &maze.tiles[4] {
             assert_eq!(*tile, Tile::External);
         }
+
+        assert_eq!(maze.tiles[2][2], Tile::Empty);
+    }
+
+    #[test]
+    fn flood_outside_squeeze_test() {
+        let mut maze = parse_input(&get_test_input_c2());
+        maze.flood_outside();
+
+        // Reached only by squeezing between the `||` pipes in the middle
+        assert_eq!(maze.tiles[3][3], Tile::External);
+        assert_eq!(maze.tiles[6][2], Tile::Empty);
+        assert_eq!(maze.tiles[6][7], Tile::Empty);
+    }
+
+    #[test]
+    fn flood_outside_large_test() {
+        let size = 1_000;
+        let mut input = vec![".".repeat(size); size];
+        input[1] = format!(".S{}7.", "-".repeat(size - 4));
+
+        for line in &mut input[2..size - 2] {
+            *line = format!(".|{}|.", ".".repeat(size - 4));
+        }
+
+        input[size - 2] = format!(".L{}J.", "-".repeat(size - 4));
+
+        let mut maze = parse_input(&input);
+        maze.flood_outside();
+
+        let outside = maze
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == Tile::External);
+        let inside = maze
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == Tile::Empty);
+
+        assert_eq!(outside.count(), 4 * size - 4);
+        assert_eq!(inside.count(), (size - 4) * (size - 4));
+    }
+
+    #[test]
+    fn render_test() {
+        let result = render(&get_test_input_c2(), RenderStyle::Plain);
+        let expected_result = "\
+OOOOOOOOOO
+OF------7O
+O|F----7|O
+O||OOOO||O
+O||OOOO||O
+O|L-7F-J|O
+O|II||II|O
+OL--JL--JO
+OOOOOOOOOO
+";
+
+        assert_eq!(result, expected_result);
+
+        let result = render(&get_test_input_b(), RenderStyle::Unicode);
+        let expected_result = "  ┌┐ \n ┌┘│ \n┌┘░└┐\n│┌──┘\n└┘   \n";
+
+        assert_eq!(result, expected_result);
+    }
+
+    #[test]
+    fn to_svg_test() {
+        let result = to_svg(&get_test_input_a());
+        let rects: Vec<_> = result
+            .lines()
+            .filter(|line| line.starts_with("<rect"))
+            .collect();
+
+        assert!(
+            result.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 50 50\"")
+        );
+        assert!(result.ends_with("</svg>\n"));
+        assert_eq!(rects.len(), 17);
+        assert_eq!(
+            rects.iter().filter(|line| line.contains("#8fd18f")).count(),
+            1
+        );
+        assert!(rects
+            .contains(&"<rect x=\"20\" y=\"20\" width=\"10\" height=\"10\" fill=\"#8fd18f\"/>"));
+        assert!(result.contains("<path d=\"M15 15L15 20M15 15L20 15M25 15L20 15"));
+        assert!(result.contains("<circle cx=\"15\" cy=\"15\" r=\"4\""));
+
+        // Junk pipes are drawn, but not as part of the loop
+        let mut input = get_test_input_a();
+        input[0] = "..F..".to_owned();
+
+        let result = to_svg(&input);
+
+        assert!(result.contains("<path d=\"M25 5L25 10M25 5L30 5\" stroke=\"#8c8c8c\""));
+    }
+
+    /// Breadth-first search over every pipe connected to `S`, with no loop tracing
+    fn farthest_oracle(input: &[String]) -> i64 {
+        let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
+        let opens = |ch: char, dx: isize, dy: isize| match (dx, dy) {
+            (0, -1) => "S|LJ".contains(ch),
+            (0, 1) => "S|7F".contains(ch),
+            (-1, 0) => "S-J7".contains(ch),
+            _ => "S-LF".contains(ch),
+        };
+
+        let start = input
+            .iter()
+            .enumerate()
+            .find_map(|(y, line)| Some((line.find('S')?, y)));
+        let mut distances = HashMap::from([(start.unwrap(), 0)]);
+        let mut queue = VecDeque::from([start.unwrap()]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                let Some(&next) = grid.get(ny as usize).and_then(|row| row.get(nx as usize)) else {
+                    continue;
+                };
+
+                let point = (nx as usize, ny as usize);
+
+                if opens(grid[y][x], dx, dy)
+                    && opens(next, -dx, -dy)
+                    && !distances.contains_key(&point)
+                {
+                    distances.insert(point, distances[&(x, y)] + 1);
+                    queue.push_back(point);
+                }
+            }
+        }
+
+        distances.into_values().max().unwrap()
+    }
+
+    /// Flood fills from the border instead of scanning rows for crossings
+    fn enclosed_oracle(input: &[String]) -> i64 {
+        let maze = Maze::parse(input).unwrap();
+        let cleared = maze.classify(&maze.trace_loop().points());
+
+        cleared
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == Tile::Empty)
+            .count() as i64
+    }
+
+    #[test]
+    fn generate_test() {
+        let smallest = generate(1, 5, 5);
+
+        assert_eq!(smallest.input.len(), 5);
+        assert_eq!(smallest.input[2].chars().nth(2), Some('.'));
+        assert_eq!(smallest.farthest, 4);
+        assert_eq!(smallest.enclosed, 1);
+
+        assert_eq!(generate(42, 30, 20), generate(42, 30, 20));
+        assert_ne!(generate(42, 30, 20), generate(43, 30, 20));
+    }
+
+    #[test]
+    fn generate_property_test() {
+        for seed in 0..200 {
+            let width = 5 + seed as usize % 37;
+            let height = 5 + seed as usize * 7 % 41;
+            let maze = generate(seed, width, height);
+
+            assert_eq!(maze.input.len(), height);
+            assert!(maze.input.iter().all(|line| line.len() == width));
+
+            assert_eq!(part1(&maze.input), maze.farthest, "seed {seed}");
+            assert_eq!(farthest_oracle(&maze.input), maze.farthest, "seed {seed}");
+            assert_eq!(part2(&maze.input), maze.enclosed, "seed {seed}");
+            assert_eq!(enclosed_oracle(&maze.input), maze.enclosed, "seed {seed}");
+        }
     }
 
     #[test]
@@ -705,26 +1313,26 @@ mod tests {
         assert_eq!(result, 1);
     }
 
-    // #[test]
-    // fn part2_ex_test_c() {
-    //     let result1 = part2(&get_test_input_c1());
-    //     assert_eq!(result1, 4);
+    #[test]
+    fn part2_ex_test_c() {
+        let result1 = part2(&get_test_input_c1());
+        assert_eq!(result1, 4);
 
-    //     let result2 = part2(&get_test_input_c2());
-    //     assert_eq!(result2, 4);
-    // }
+        let result2 = part2(&get_test_input_c2());
+        assert_eq!(result2, 4);
+    }
 
-    // #[test]
-    // fn part2_ex_test_d() {
-    //     let result = part2(&get_test_input_d());
+    #[test]
+    fn part2_ex_test_d() {
+        let result = part2(&get_test_input_d());
 
-    //     assert_eq!(result, 8);
-    // }
+        assert_eq!(result, 8);
+    }
 
-    // #[test]
-    // fn part2_ex_test_e() {
-    //     let result = part2(&get_test_input_e());
+    #[test]
+    fn part2_ex_test_e() {
+        let result = part2(&get_test_input_e());
 
-    //     assert_eq!(result, 10);
-    // }
+        assert_eq!(result, 10);
+    }
 }
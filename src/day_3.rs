@@ -1,50 +1,327 @@
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+
+pub use crate::render::RenderStyle;
+
+/// Characters that separate entries without being symbols
+pub const BACKGROUND: &[char] = &['.', ' '];
+
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
-    let (number_list, symbol_list) = parse_input(input);
-
-    number_list
-        .iter()
-        .filter_map(|num_entry| {
-            if symbol_list
-                .iter()
-                .any(|sym_entry| is_adjacent(sym_entry, num_entry))
-            {
-                Some(num_entry.number)
-            } else {
-                None
-            }
-        })
-        .sum()
+    parse_input(input).part_number_sum()
 }
 
 #[must_use]
 pub fn part2(input: &[String]) -> i64 {
-    let (number_list, symbol_list) = parse_input(input);
+    gear_ratios(&GEAR_RULE, input)
+}
+
+/// Sums the value of every gear in the schematic, where what counts as a gear and how its
+/// adjacent part numbers are combined is decided by `rule`
+#[must_use]
+pub fn gear_ratios(rule: &GearRule, input: &[String]) -> i64 {
+    parse_input(input).gear_ratios(rule)
+}
+
+/// Reprints the schematic, highlighting part numbers, non-part numbers, gears and the gear
+/// symbols that did not satisfy `rule`
+///
+/// [`RenderStyle::Ansi`] colors each entry in place. [`RenderStyle::Plain`] and
+/// [`RenderStyle::Unicode`] print a marker row under each schematic row:
+///
+/// * `^` part number
+/// * `x` number not adjacent to any symbol
+/// * `G` gear
+/// * `!` gear symbol that fails the gear rule
+#[must_use]
+pub fn render(input: &[String], rule: &GearRule, style: RenderStyle) -> String {
+    parse_input(input).render(rule, style)
+}
+
+/// A tokenized engine schematic, with rows and columns counted in characters
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schematic {
+    rows: Vec<Vec<char>>,
+    numbers: Vec<NumberEntry>,
+    symbols: Vec<SymbolEntry>,
+}
+
+impl Schematic {
+    /// Tokenizes the schematic, treating every character in `background` as empty space.
+    /// A trailing `'\r'` on each line is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * A line contains a control character
+    /// * A number does not fit in an `i64`
+    pub fn parse(input: &[String], background: &[char]) -> Result<Self, ParseError> {
+        let mut rows = Vec::with_capacity(input.len());
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+
+        for (row_num, text) in input.iter().enumerate() {
+            let chars: Vec<char> = text.strip_suffix('\r').unwrap_or(text).chars().collect();
+            let mut index = 0;
+
+            loop {
+                let ind_begin = index;
+                let tk =
+                    get_next_token(&chars, &mut index, background).map_err(|kind| ParseError {
+                        line: row_num + 1,
+                        column: ind_begin + 1,
+                        kind,
+                    })?;
+
+                match tk {
+                    Token::Number(n) => {
+                        numbers.push(NumberEntry::new(n, row_num, (ind_begin, index - 1)));
+                    }
+                    Token::Symbol(c) => symbols.push(SymbolEntry::new(c, row_num, ind_begin)),
+                    Token::Spacer => {}
+                    Token::End => break,
+                }
+            }
+
+            rows.push(chars);
+        }
+
+        Ok(Self {
+            rows,
+            numbers,
+            symbols,
+        })
+    }
+
+    /// Sums every number adjacent to at least one symbol
+    #[must_use]
+    pub fn part_number_sum(&self) -> i64 {
+        self.numbers
+            .iter()
+            .filter(|num_entry| self.is_part_number(num_entry))
+            .map(|num_entry| num_entry.number)
+            .sum()
+    }
+
+    /// See [`gear_ratios`]
+    #[must_use]
+    pub fn gear_ratios(&self, rule: &GearRule) -> i64 {
+        self.symbols
+            .iter()
+            .filter_map(|sym_entry| self.gear_value(rule, sym_entry))
+            .sum()
+    }
+
+    /// See [`render`]
+    #[must_use]
+    pub fn render(&self, rule: &GearRule, style: RenderStyle) -> String {
+        let mut marks: Vec<Vec<Option<Mark>>> =
+            self.rows.iter().map(|row| vec![None; row.len()]).collect();
+
+        for num_entry in &self.numbers {
+            let mark = if self.is_part_number(num_entry) {
+                Mark::PartNumber
+            } else {
+                Mark::Number
+            };
+
+            marks[num_entry.row][num_entry.range.0..=num_entry.range.1].fill(Some(mark));
+        }
 
-    symbol_list
-        .iter()
-        .filter_map(|sym_entry| {
-            if sym_entry.symbol != '*' {
-                return None;
+        for sym_entry in self
+            .symbols
+            .iter()
+            .filter(|sym_entry| rule.symbols.contains(&sym_entry.symbol))
+        {
+            marks[sym_entry.row][sym_entry.column] = if self.gear_value(rule, sym_entry).is_some() {
+                Some(Mark::Gear)
+            } else {
+                Some(Mark::NotGear)
+            };
+        }
+
+        let mut output = String::new();
+
+        for (row, row_marks) in self.rows.iter().zip(&marks) {
+            match style {
+                RenderStyle::Ansi => {
+                    for (ch, mark) in row.iter().zip(row_marks) {
+                        match mark {
+                            Some(mark) => {
+                                let _ = write!(output, "\x1b[{}m{ch}\x1b[0m", mark.ansi_code());
+                            }
+                            None => output.push(*ch),
+                        }
+                    }
+                }
+                RenderStyle::Plain | RenderStyle::Unicode => {
+                    output.extend(row);
+                    output.push('\n');
+                    output.push_str(
+                        row_marks
+                            .iter()
+                            .map(|mark| mark.map_or(' ', Mark::marker))
+                            .collect::<String>()
+                            .trim_end(),
+                    );
+                }
             }
 
-            let matches = number_list.iter().filter_map(|num_entry| {
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn is_part_number(&self, num_entry: &NumberEntry) -> bool {
+        self.symbols
+            .iter()
+            .any(|sym_entry| is_adjacent(sym_entry, num_entry))
+    }
+
+    fn gear_value(&self, rule: &GearRule, sym_entry: &SymbolEntry) -> Option<i64> {
+        if !rule.symbols.contains(&sym_entry.symbol) {
+            return None;
+        }
+
+        let matches: Vec<i64> = self
+            .numbers
+            .iter()
+            .filter_map(|num_entry| {
                 if is_adjacent(sym_entry, num_entry) {
                     Some(num_entry.number)
                 } else {
                     None
                 }
-            });
+            })
+            .collect();
 
-            if matches.clone().count() == 2 {
-                Some(matches.product::<i64>())
-            } else {
-                None
-            }
-        })
-        .sum()
+        if rule.adjacent.accepts(matches.len()) {
+            rule.combine.apply(&matches)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the schematic
+    pub line: usize,
+    /// 1-based character column within the line
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    InvalidChar(char),
+    NumberTooLarge,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        match self.kind {
+            ParseErrorKind::InvalidChar(ch) => write!(f, "invalid character {ch:?}"),
+            ParseErrorKind::NumberTooLarge => write!(f, "number is too large"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mark {
+    PartNumber,
+    Number,
+    Gear,
+    NotGear,
+}
+
+impl Mark {
+    const fn ansi_code(self) -> &'static str {
+        match self {
+            Self::PartNumber => "92",
+            Self::Number => "91",
+            Self::Gear => "1;93",
+            Self::NotGear => "95",
+        }
+    }
+
+    const fn marker(self) -> char {
+        match self {
+            Self::PartNumber => '^',
+            Self::Number => 'x',
+            Self::Gear => 'G',
+            Self::NotGear => '!',
+        }
+    }
+}
+
+/// How many part numbers must be adjacent to a symbol for it to count as a gear
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdjacentCount {
+    Exactly(usize),
+    AtLeast(usize),
+    /// Inclusive on both ends
+    Between(usize, usize),
+}
+
+impl AdjacentCount {
+    #[must_use]
+    pub const fn accepts(self, count: usize) -> bool {
+        match self {
+            Self::Exactly(n) => count == n,
+            Self::AtLeast(n) => count >= n,
+            Self::Between(min, max) => count >= min && count <= max,
+        }
+    }
+}
+
+/// How the part numbers adjacent to a gear are combined into its value
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Combine {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Combine {
+    /// Returns `None` if there is nothing to combine and the operation has no identity value
+    #[must_use]
+    pub fn apply(self, numbers: &[i64]) -> Option<i64> {
+        match self {
+            Self::Product => Some(numbers.iter().product()),
+            Self::Sum => Some(numbers.iter().sum()),
+            Self::Max => numbers.iter().max().copied(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GearRule<'a> {
+    pub symbols: &'a [char],
+    pub adjacent: AdjacentCount,
+    pub combine: Combine,
 }
 
+impl<'a> GearRule<'a> {
+    #[must_use]
+    pub const fn new(symbols: &'a [char], adjacent: AdjacentCount, combine: Combine) -> Self {
+        Self {
+            symbols,
+            adjacent,
+            combine,
+        }
+    }
+}
+
+/// The rule from the puzzle: a `'*'` next to exactly two part numbers, multiplied together
+pub const GEAR_RULE: GearRule = GearRule::new(&['*'], AdjacentCount::Exactly(2), Combine::Product);
+
 #[derive(Debug, Eq, PartialEq)]
 enum Token {
     Number(i64),
@@ -83,59 +360,43 @@ impl SymbolEntry {
     }
 }
 
-fn get_next_token(bytes: &[u8], index: &mut usize) -> Token {
-    if *index < bytes.len() {
-        *index += 1;
-        match bytes[*index - 1] {
-            b'.' => Token::Spacer,
-            ch => {
-                if ch.is_ascii_digit() {
-                    let mut num_str = String::new();
-
-                    num_str.push(bytes[*index - 1] as char);
-
-                    while *index < bytes.len() && bytes[*index].is_ascii_digit() {
-                        num_str.push(bytes[*index] as char);
-                        *index += 1;
-                    }
-
-                    Token::Number(num_str.parse().unwrap())
-                } else {
-                    Token::Symbol(ch as char)
-                }
-            }
-        }
-    } else {
-        Token::End
+fn get_next_token(
+    chars: &[char],
+    index: &mut usize,
+    background: &[char],
+) -> Result<Token, ParseErrorKind> {
+    if *index >= chars.len() {
+        return Ok(Token::End);
     }
-}
 
-fn parse_input(input: &[String]) -> (Vec<NumberEntry>, Vec<SymbolEntry>) {
-    let mut tk: Token = Token::Spacer;
-    let mut number_list = Vec::new();
-    let mut symbol_list = Vec::new();
+    let ch = chars[*index];
+    *index += 1;
 
-    for (row_num, text) in input.iter().enumerate() {
-        let mut index = 0;
-        while tk != Token::End {
-            let ind_begin = index;
-            tk = get_next_token(text.as_bytes(), &mut index);
-            let ind_end = index - 1;
+    if background.contains(&ch) {
+        Ok(Token::Spacer)
+    } else if ch.is_ascii_digit() {
+        let mut num_str = String::new();
 
-            match tk {
-                Token::Number(n) => {
-                    number_list.push(NumberEntry::new(n, row_num, (ind_begin, ind_end)));
-                }
+        num_str.push(ch);
 
-                Token::Symbol(c) => symbol_list.push(SymbolEntry::new(c, row_num, ind_begin)),
-                _ => {}
-            }
+        while *index < chars.len() && chars[*index].is_ascii_digit() {
+            num_str.push(chars[*index]);
+            *index += 1;
         }
 
-        tk = Token::Spacer;
+        num_str
+            .parse()
+            .map(Token::Number)
+            .map_err(|_| ParseErrorKind::NumberTooLarge)
+    } else if ch.is_control() {
+        Err(ParseErrorKind::InvalidChar(ch))
+    } else {
+        Ok(Token::Symbol(ch))
     }
+}
 
-    (number_list, symbol_list)
+fn parse_input(input: &[String]) -> Schematic {
+    Schematic::parse(input, BACKGROUND).unwrap()
 }
 
 const fn is_adjacent(sym_entry: &SymbolEntry, num_entry: &NumberEntry) -> bool {
@@ -184,10 +445,10 @@ mod tests {
             column: 3,
         }];
 
-        let (numbers, symbols) = parse_input(&get_test_input()[0..=1]);
+        let schematic = parse_input(&get_test_input()[0..=1]);
 
-        assert_eq!(numbers, expected_numbers);
-        assert_eq!(symbols, expected_symbols);
+        assert_eq!(schematic.numbers, expected_numbers);
+        assert_eq!(schematic.symbols, expected_symbols);
     }
 
     #[test]
@@ -203,4 +464,99 @@ mod tests {
 
         assert_eq!(result, 467_835);
     }
+
+    #[test]
+    fn gear_ratios_test() {
+        let result = gear_ratios(&GEAR_RULE, &get_test_input());
+
+        assert_eq!(result, part2(&get_test_input()));
+
+        let sum_rule = GearRule::new(&['*'], AdjacentCount::Exactly(2), Combine::Sum);
+        let result = gear_ratios(&sum_rule, &get_test_input());
+
+        assert_eq!(result, 467 + 35 + 755 + 598);
+
+        let any_rule = GearRule::new(
+            &['*', '#', '+', '$'],
+            AdjacentCount::Between(1, 2),
+            Combine::Max,
+        );
+        let result = gear_ratios(&any_rule, &get_test_input());
+
+        assert_eq!(result, 467 + 633 + 617 + 592 + 664 + 755);
+    }
+
+    #[test]
+    fn render_test() {
+        let input = &get_test_input()[0..=2];
+        let result = render(input, &GEAR_RULE, RenderStyle::Plain);
+
+        assert_eq!(
+            result,
+            "467..114..\n^^^  xxx\n...*......\n   G\n..35..633.\n  ^^  xxx\n"
+        );
+
+        let result = render(&input[0..=1], &GEAR_RULE, RenderStyle::Ansi);
+
+        assert_eq!(
+            result,
+            "\x1b[92m4\x1b[0m\x1b[92m6\x1b[0m\x1b[92m7\x1b[0m..\x1b[91m1\x1b[0m\x1b[91m1\x1b[0m\x1b[91m4\x1b[0m..\n...\x1b[95m*\x1b[0m......\n"
+        );
+    }
+
+    #[test]
+    fn tokenizer_test() {
+        let input = [
+            "467··114 \r".to_owned(),
+            "   é  ".to_owned(),
+            "  35··633 ".to_owned(),
+        ];
+        let schematic = Schematic::parse(&input, &['·', ' ']).unwrap();
+
+        assert_eq!(
+            schematic.numbers,
+            vec![
+                NumberEntry::new(467, 0, (0, 2)),
+                NumberEntry::new(114, 0, (5, 7)),
+                NumberEntry::new(35, 2, (2, 3)),
+                NumberEntry::new(633, 2, (6, 8)),
+            ]
+        );
+        assert_eq!(schematic.symbols, vec![SymbolEntry::new('é', 1, 3)]);
+        assert_eq!(schematic.part_number_sum(), 467 + 35);
+
+        let input = ["467..\t.114".to_owned()];
+        let result = Schematic::parse(&input, BACKGROUND);
+
+        assert_eq!(
+            result,
+            Err(ParseError {
+                line: 1,
+                column: 6,
+                kind: ParseErrorKind::InvalidChar('\t'),
+            })
+        );
+
+        let input = ["..99999999999999999999".to_owned()];
+        let result = Schematic::parse(&input, BACKGROUND);
+
+        assert_eq!(
+            result,
+            Err(ParseError {
+                line: 1,
+                column: 3,
+                kind: ParseErrorKind::NumberTooLarge,
+            })
+        );
+    }
+
+    #[test]
+    fn adjacent_count_test() {
+        assert!(AdjacentCount::Exactly(2).accepts(2));
+        assert!(!AdjacentCount::Exactly(2).accepts(3));
+        assert!(AdjacentCount::AtLeast(1).accepts(4));
+        assert!(!AdjacentCount::AtLeast(1).accepts(0));
+        assert!(AdjacentCount::Between(1, 3).accepts(3));
+        assert!(!AdjacentCount::Between(1, 3).accepts(4));
+    }
 }
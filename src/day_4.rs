@@ -1,63 +1,340 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
-    let mut score = 0;
+    total_score(Scoring::Doubling, input)
+}
+
+#[must_use]
+pub fn part2(input: &[String]) -> i64 {
+    // Clamping never fails
+    card_copies(Overflow::Clamp, input)
+        .unwrap_or_default()
+        .iter()
+        .sum()
+}
+
+/// Sums the score of every card according to `scoring`
+#[must_use]
+pub fn total_score(scoring: Scoring, input: &[String]) -> i64 {
+    parse_input(input)
+        .iter()
+        .map(|card| scoring.score(card.match_count()))
+        .sum()
+}
 
-    for line in input {
-        let (win, play) = parse_numbers(line);
-        let match_count = get_win_count(&win, &play);
+/// How many points a card is worth given its number of matches
+#[derive(Clone, Copy, Debug)]
+pub enum Scoring {
+    /// 1 point for the first match, doubled for every match after it
+    Doubling,
+    /// 1 point per match
+    Linear,
+    Custom(fn(u32) -> i64),
+}
 
-        if match_count != 0 {
-            score += 2i64.pow(match_count - 1);
+impl Scoring {
+    #[must_use]
+    pub fn score(self, match_count: u32) -> i64 {
+        match self {
+            Self::Doubling => {
+                if match_count == 0 {
+                    0
+                } else {
+                    2i64.pow(match_count - 1)
+                }
+            }
+            Self::Linear => i64::from(match_count),
+            Self::Custom(score_fn) => score_fn(match_count),
         }
     }
+}
 
-    score
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Scratchcard {
+    pub id: u32,
+    pub winning: Vec<u32>,
+    pub played: Vec<u32>,
 }
 
-#[must_use]
-pub fn part2(input: &[String]) -> i64 {
-    let length = input.len();
+impl Scratchcard {
+    /// Counts how many played numbers are also winning numbers
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn match_count(&self) -> u32 {
+        let winning = NumberSet::new(&self.winning);
+
+        self.played.iter().filter(|&&n| winning.contains(n)).count() as u32
+    }
+
+    fn parse(line: &str) -> Result<Self, ParseErrorKind> {
+        let (label, numbers) = line.split_once(':').ok_or(ParseErrorKind::Malformed)?;
+        let (winning, played) = numbers.split_once('|').ok_or(ParseErrorKind::Malformed)?;
+
+        let id = label
+            .strip_prefix("Card")
+            .ok_or(ParseErrorKind::Malformed)?
+            .trim();
+
+        Ok(Self {
+            id: id
+                .parse()
+                .map_err(|_| ParseErrorKind::InvalidNumber(id.to_owned()))?,
+            winning: get_numbers(winning)?,
+            played: get_numbers(played)?,
+        })
+    }
+}
+
+/// Parses every card, checking that card IDs count up from 1 and that no number is repeated
+/// within a card's winning or played numbers
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * A line is not of the form `Card <id>: <numbers> | <numbers>`
+/// * A card ID or number is not a non-negative integer
+/// * A card ID is out of sequence
+/// * A number is repeated within the winning or played numbers
+pub fn parse_cards(input: &[String]) -> Result<Vec<Scratchcard>, ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let to_error = |kind| ParseError {
+                line: index + 1,
+                kind,
+            };
+
+            let card = Scratchcard::parse(line).map_err(to_error)?;
+
+            let expected = u32::try_from(index + 1).unwrap_or(u32::MAX);
+
+            if card.id != expected {
+                return Err(to_error(ParseErrorKind::OutOfSequence {
+                    expected,
+                    found: card.id,
+                }));
+            }
+
+            for numbers in [&card.winning, &card.played] {
+                if let Some(number) = find_duplicate(numbers) {
+                    return Err(to_error(ParseErrorKind::Duplicate(number)));
+                }
+            }
+
+            Ok(card)
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the card table
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    Malformed,
+    InvalidNumber(String),
+    OutOfSequence { expected: u32, found: u32 },
+    Duplicate(u32),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            ParseErrorKind::Malformed => write!(f, "expected `Card <id>: <numbers> | <numbers>`"),
+            ParseErrorKind::InvalidNumber(s) => write!(f, "invalid number {s:?}"),
+            ParseErrorKind::OutOfSequence { expected, found } => {
+                write!(f, "expected card {expected}, found card {found}")
+            }
+            ParseErrorKind::Duplicate(n) => write!(f, "number {n} is repeated"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// What to do when a card wins copies of cards past the end of the table
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overflow {
+    /// Ignore the copies that would land past the last card
+    Clamp,
+    /// Report an [`OverflowError`]
+    Error,
+    /// Continue counting from the first card. Those cards have already been scored, so the
+    /// extra copies are counted but do not win any further cards.
+    Wrap,
+}
+
+/// A card won copies of cards past the end of the table
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OverflowError {
+    /// 1-based position of the card in the table
+    pub card: usize,
+    pub win_count: u32,
+    /// Number of cards after this one in the table
+    pub remaining: usize,
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "card {} wins {} copies but only {} cards follow it",
+            self.card, self.win_count, self.remaining
+        )
+    }
+}
+
+impl Error for OverflowError {}
+
+/// Returns how many copies of each card end up being held, in table order
+///
+/// # Errors
+///
+/// This function will return an error if `overflow` is [`Overflow::Error`] and a card wins
+/// copies of cards past the end of the table
+pub fn card_copies(overflow: Overflow, input: &[String]) -> Result<Vec<i64>, OverflowError> {
+    let cards = parse_input(input);
+    let length = cards.len();
     let mut trackers = vec![1; length];
 
-    for i in 0..length {
-        let (win, play) = parse_numbers(&input[i]);
-        let win_count = get_win_count(&win, &play);
+    for (i, card) in cards.iter().enumerate() {
+        let win_count = card.match_count();
+        let copies = trackers[i];
+        let remaining = length - i - 1;
+
+        if win_count as usize > remaining && overflow == Overflow::Error {
+            return Err(OverflowError {
+                card: i + 1,
+                win_count,
+                remaining,
+            });
+        }
 
         for j in 0..win_count as usize {
-            trackers[i + j + 1] += trackers[i];
+            let target = i + j + 1;
+
+            match overflow {
+                Overflow::Clamp | Overflow::Error => {
+                    if target >= length {
+                        break;
+                    }
+
+                    trackers[target] += copies;
+                }
+                Overflow::Wrap => trackers[target % length] += copies,
+            }
         }
     }
 
-    trackers.iter().sum()
+    Ok(trackers)
 }
 
-fn parse_numbers(line: &str) -> (Vec<i64>, Vec<i64>) {
-    let win_start = line.find(':').unwrap();
-    let play_start = line.find('|').unwrap();
-
-    (
-        get_numbers(&line[win_start..play_start]),
-        get_numbers(&line[play_start..]),
-    )
+/// Set of the numbers on a card, as a fixed-size bitset when they are all below
+/// [`NumberSet::BITSET_LIMIT`] and as a sorted list otherwise
+enum NumberSet {
+    Bits([u64; 2]),
+    Sorted(Vec<u32>),
 }
 
-fn get_numbers(line: &str) -> Vec<i64> {
-    let num_split: Vec<&str> = line.split_whitespace().collect();
-    let mut num_list = Vec::new();
+impl NumberSet {
+    /// Numbers below this fit in the bitset, which covers every real card
+    const BITSET_LIMIT: u32 = 128;
 
-    for seq in num_split
-        .iter()
-        .filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
-    {
-        num_list.push(seq.parse().unwrap());
+    fn new(numbers: &[u32]) -> Self {
+        let mut set = Self::with_max(numbers.iter().max().copied().unwrap_or(0));
+
+        for &n in numbers {
+            set.insert(n);
+        }
+
+        set
     }
 
-    num_list
+    fn with_max(max: u32) -> Self {
+        if max < Self::BITSET_LIMIT {
+            Self::Bits([0; 2])
+        } else {
+            Self::Sorted(Vec::new())
+        }
+    }
+
+    /// Returns `false` if `n` was already in the set
+    fn insert(&mut self, n: u32) -> bool {
+        match self {
+            Self::Bits(words) => {
+                let was_present = words[(n / 64) as usize] & (1 << (n % 64)) != 0;
+                words[(n / 64) as usize] |= 1 << (n % 64);
+
+                !was_present
+            }
+            Self::Sorted(numbers) => match numbers.binary_search(&n) {
+                Ok(_) => false,
+                Err(index) => {
+                    numbers.insert(index, n);
+
+                    true
+                }
+            },
+        }
+    }
+
+    fn contains(&self, n: u32) -> bool {
+        match self {
+            Self::Bits(words) => words
+                .get((n / 64) as usize)
+                .is_some_and(|word| word & (1 << (n % 64)) != 0),
+            Self::Sorted(numbers) => numbers.binary_search(&n).is_ok(),
+        }
+    }
+}
+
+fn find_duplicate(numbers: &[u32]) -> Option<u32> {
+    let mut seen = NumberSet::with_max(numbers.iter().max().copied().unwrap_or(0));
+
+    numbers.iter().find(|&&n| !seen.insert(n)).copied()
+}
+
+fn get_numbers(line: &str) -> Result<Vec<u32>, ParseErrorKind> {
+    line.split_whitespace()
+        .map(|s| {
+            s.parse()
+                .map_err(|_| ParseErrorKind::InvalidNumber(s.to_owned()))
+        })
+        .collect()
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn get_win_count(win_nums: &[i64], play_nums: &[i64]) -> u32 {
-    play_nums.iter().filter(|n| win_nums.contains(n)).count() as u32
+/// Parses every card without the checks [`parse_cards`] makes, so that any slice of a card
+/// table can be solved
+///
+/// # Panics
+///
+/// Panics if a line is not a card, see [`ParseErrorKind`]
+fn parse_input(input: &[String]) -> Vec<Scratchcard> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            Scratchcard::parse(line).unwrap_or_else(|kind| {
+                panic!(
+                    "{}",
+                    ParseError {
+                        line: index + 1,
+                        kind
+                    }
+                )
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -76,14 +353,80 @@ mod tests {
     }
 
     #[test]
-    fn parse_numbers_test() {
-        let expected_wins = vec![41, 48, 83, 86, 17];
-        let expected_nums = vec![83, 86, 6, 31, 17, 9, 48, 53];
+    fn parse_cards_test() {
+        let expected_card = Scratchcard {
+            id: 1,
+            winning: vec![41, 48, 83, 86, 17],
+            played: vec![83, 86, 6, 31, 17, 9, 48, 53],
+        };
+
+        let cards = parse_cards(&get_test_input()).unwrap();
+
+        assert_eq!(cards.len(), 6);
+        assert_eq!(cards[0], expected_card);
+        assert_eq!(cards[0].match_count(), 4);
 
-        let (wins, nums) = parse_numbers(&get_test_input()[0]);
+        let result = parse_cards(&["Card 2: 1 2 | 3 4".to_owned()]);
 
-        assert_eq!(wins, expected_wins);
-        assert_eq!(nums, expected_nums);
+        assert_eq!(
+            result,
+            Err(ParseError {
+                line: 1,
+                kind: ParseErrorKind::OutOfSequence {
+                    expected: 1,
+                    found: 2,
+                },
+            })
+        );
+
+        let result = parse_cards(&["Card 1: 1 2 | 3 3".to_owned()]);
+
+        assert_eq!(
+            result,
+            Err(ParseError {
+                line: 1,
+                kind: ParseErrorKind::Duplicate(3),
+            })
+        );
+
+        let result = parse_cards(&["Card 1: 1 x | 3 4".to_owned()]);
+
+        assert_eq!(
+            result,
+            Err(ParseError {
+                line: 1,
+                kind: ParseErrorKind::InvalidNumber("x".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn large_numbers_test() {
+        let card = Scratchcard {
+            id: 1,
+            winning: vec![4_000_000_000, 7, 200],
+            played: vec![200, 4_000_000_000, 8, 127],
+        };
+
+        assert_eq!(card.match_count(), 2);
+        assert_eq!(
+            find_duplicate(&[4_000_000_000, 3, 4_000_000_000]),
+            Some(4_000_000_000)
+        );
+        assert_eq!(find_duplicate(&[127, 3, 64]), None);
+    }
+
+    #[test]
+    fn total_score_test() {
+        assert_eq!(total_score(Scoring::Doubling, &get_test_input()), 13);
+        assert_eq!(
+            total_score(Scoring::Linear, &get_test_input()),
+            4 + 2 + 2 + 1
+        );
+        assert_eq!(
+            total_score(Scoring::Custom(|n| i64::from(n * n)), &get_test_input()),
+            16 + 4 + 4 + 1
+        );
     }
 
     #[test]
@@ -99,4 +442,28 @@ mod tests {
 
         assert_eq!(result, 30);
     }
+
+    #[test]
+    fn card_copies_test() {
+        let result = card_copies(Overflow::Error, &get_test_input());
+
+        assert_eq!(result, Ok(vec![1, 2, 4, 8, 14, 1]));
+
+        let input = &get_test_input()[0..3];
+
+        assert_eq!(card_copies(Overflow::Clamp, input), Ok(vec![1, 2, 4]));
+        assert_eq!(
+            card_copies(Overflow::Clamp, &get_test_input()[2..]),
+            Ok(vec![1, 2, 4, 1])
+        );
+        assert_eq!(card_copies(Overflow::Wrap, input), Ok(vec![10, 8, 5]));
+        assert_eq!(
+            card_copies(Overflow::Error, input),
+            Err(OverflowError {
+                card: 1,
+                win_count: 4,
+                remaining: 2,
+            })
+        );
+    }
 }
@@ -27,6 +27,23 @@ pub fn part2(input: &[String]) -> i64 {
         .unwrap_or(0)
 }
 
+/// [`part2`] by mapping every seed of every range forwards instead of searching backwards from
+/// location 0
+///
+/// Far too slow for the real input, but a reference for the reverse search.
+#[must_use]
+pub fn part2_forward(input: &[String]) -> i64 {
+    let almanac = parse_input(input);
+
+    almanac
+        .seeds
+        .chunks_exact(2)
+        .flat_map(|chunk| chunk[0]..chunk[0] + chunk[1])
+        .map(|seed| almanac.seed_to_location(seed))
+        .min()
+        .unwrap_or(0)
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct RangeMap {
     dest_start: i64,
@@ -262,4 +279,11 @@ mod tests {
 
         assert_eq!(result, 46);
     }
+
+    #[test]
+    fn part2_forward_test() {
+        let result = part2_forward(&get_test_input());
+
+        assert_eq!(result, 46);
+    }
 }
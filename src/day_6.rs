@@ -1,19 +1,50 @@
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
+    part1_with(Method::DEFAULT, input)
+}
+
+#[must_use]
+pub fn part2(input: &[String]) -> i64 {
+    part2_with(Method::DEFAULT, input)
+}
+
+/// How the range of winning button times is found
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Method {
+    /// Double-ended linear search
+    Linear,
+    /// Solves `distance = time * (race_time - time)` directly. Faster, but floating point makes
+    /// it prone to off by one errors (ex: f32 fails)
+    Quadratic,
+}
+
+impl Method {
+    /// `Quadratic` with the `fast_math` feature, `Linear` otherwise
+    pub const DEFAULT: Self = if cfg!(feature = "fast_math") {
+        Self::Quadratic
+    } else {
+        Self::Linear
+    };
+}
+
+/// [`part1`] using a specific [`Method`], regardless of the `fast_math` feature
+#[must_use]
+pub fn part1_with(method: Method, input: &[String]) -> i64 {
     let records = parse_input1(input);
 
     records
         .iter()
-        .map(RaceRecord::get_button_time_record_range)
+        .map(|record| record.get_button_time_record_range(method))
         .map(|(min, max)| max - min + 1)
         .product()
 }
 
+/// [`part2`] using a specific [`Method`], regardless of the `fast_math` feature
 #[must_use]
-pub fn part2(input: &[String]) -> i64 {
+pub fn part2_with(method: Method, input: &[String]) -> i64 {
     let record = parse_input2(input);
 
-    let (min, max) = record.get_button_time_record_range();
+    let (min, max) = record.get_button_time_record_range(method);
 
     max - min + 1
 }
@@ -66,14 +97,19 @@ struct RaceRecord {
 }
 
 impl RaceRecord {
-    #[cfg(not(feature = "fast_math"))]
-    fn get_button_time_record_range(&self) -> (i64, i64) {
+    fn get_button_time_record_range(&self, method: Method) -> (i64, i64) {
+        match method {
+            Method::Linear => self.linear_range(),
+            Method::Quadratic => self.quadratic_range(),
+        }
+    }
+
+    fn linear_range(&self) -> (i64, i64) {
         let check_record = |time: i64| {
             let distance = (self.time - time) * time;
             distance > self.distance
         };
 
-        // Option 1: Double-ended linear search
         let min = (1..self.time).find(|num| check_record(*num)).unwrap_or(0);
 
         let max = (1..self.time)
@@ -84,10 +120,7 @@ impl RaceRecord {
         (min, max)
     }
 
-    #[cfg(feature = "fast_math")]
-    fn get_button_time_record_range(&self) -> (i64, i64) {
-        // Option 2: Linear equation (distance = time * (self.time - time))
-        // - Faster, but potential for off by one errors (ex: f32 fails)
+    fn quadratic_range(&self) -> (i64, i64) {
         let ftime = self.time as f64;
 
         let dev = f64::sqrt(4.0f64.mul_add(-(self.distance as f64), ftime.powi(2)));
@@ -163,4 +196,12 @@ mod tests {
 
         assert_eq!(result, 71_503);
     }
+
+    #[test]
+    fn method_test() {
+        for method in [Method::Linear, Method::Quadratic] {
+            assert_eq!(part1_with(method, &get_test_input()), 288);
+            assert_eq!(part2_with(method, &get_test_input()), 71_503);
+        }
+    }
 }
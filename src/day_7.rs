@@ -1,133 +1,466 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
 
+/// # Panics
+///
+/// Panics if the input contains a malformed hand
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
-    let mut hands = parse_input(input, false);
+    total_winnings(&RuleSet::standard(), input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics if the input contains a malformed hand
+#[must_use]
+pub fn part2(input: &[String]) -> i64 {
+    total_winnings(&RuleSet::jokers(), input).unwrap()
+}
+
+/// Ranks every hand under `rules` and sums each wager multiplied by its rank
+///
+/// # Errors
+///
+/// This function will return an error if a hand is malformed, see [`ParseErrorKind`]
+pub fn total_winnings(rules: &RuleSet, input: &[String]) -> Result<i64, ParseError> {
+    Ok(ranking_report(rules, input)?
+        .iter()
+        .map(|hand| hand.winnings)
+        .sum())
+}
+
+/// How a single hand was ranked
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankedHand {
+    pub cards: String,
+    /// The cards with every wild card replaced by the card it stood in for
+    pub resolved: String,
+    pub category: &'static str,
+    /// 1-based, weakest hand first
+    pub rank: usize,
+    pub wager: i64,
+    pub winnings: i64,
+}
+
+/// Ranks every hand under `rules`, returning the hands from weakest to strongest
+///
+/// # Errors
+///
+/// This function will return an error if a hand is malformed, see [`ParseErrorKind`]
+pub fn ranking_report(rules: &RuleSet, input: &[String]) -> Result<Vec<RankedHand>, ParseError> {
+    let mut hands = parse_input(input, rules)?;
     hands.sort();
 
-    hands
+    Ok(hands
         .iter()
         .enumerate()
-        .map(|(x, y)| (x + 1) as i64 * y.wager)
-        .sum()
+        .map(|(index, hand)| RankedHand {
+            cards: rules.card_labels(&hand.cards),
+            resolved: rules.card_labels(&hand.resolved),
+            category: rules.categories[hand.value].name,
+            rank: index + 1,
+            wager: hand.wager,
+            winnings: (index + 1) as i64 * hand.wager,
+        })
+        .collect())
 }
 
+/// Formats a report from [`ranking_report`] as a table
 #[must_use]
-pub fn part2(input: &[String]) -> i64 {
-    let mut hands = parse_input(input, true);
-    hands.sort();
+pub fn format_report(report: &[RankedHand]) -> String {
+    let cards_width = report
+        .iter()
+        .map(|hand| hand.cards.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Cards".len());
 
-    hands
+    let resolved_width = cards_width.max("Played as".len());
+
+    let category_width = report
         .iter()
-        .enumerate()
-        .map(|(x, y)| (x + 1) as i64 * y.wager)
-        .sum()
+        .map(|hand| hand.category.len())
+        .max()
+        .unwrap_or(0)
+        .max("Category".len());
+
+    let mut output = format!(
+        "{:>5}  {:<cards_width$}  {:<resolved_width$}  {:<category_width$}  {:>6}  {:>10}\n",
+        "Rank", "Cards", "Played as", "Category", "Wager", "Winnings"
+    );
+
+    for hand in report {
+        let _ = writeln!(
+            output,
+            "{:>5}  {:<cards_width$}  {:<resolved_width$}  {:<category_width$}  {:>6}  {:>10}",
+            hand.rank, hand.cards, hand.resolved, hand.category, hand.wager, hand.winnings
+        );
+    }
+
+    output
 }
 
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
-enum HandValue {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfKind,
-    FullHouse,
-    FourOfKind,
-    FiveOfKind,
+/// Ranking reports for both parts, as printed by the runner
+#[must_use]
+pub fn report(input: &[String]) -> String {
+    let format_part = |rules| match ranking_report(&rules, input) {
+        Ok(report) => format_report(&report),
+        Err(err) => format!("{err}\n"),
+    };
+
+    format!(
+        "Part 1\n{}\nPart 2\n{}",
+        format_part(RuleSet::standard()),
+        format_part(RuleSet::jokers())
+    )
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
-struct Hand {
-    cards: [u8; 5],
-    wager: i64,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the input
+    pub line: usize,
+    /// 1-based character column within the line
+    pub column: usize,
+    pub kind: ParseErrorKind,
 }
 
-impl Hand {
-    fn get_value(&self) -> HandValue {
-        let mut char_map: HashMap<u8, u32> = HashMap::new();
-
-        self.cards.iter().for_each(|num| {
-            char_map
-                .entry(*num)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
-        });
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    MissingCards,
+    WrongHandLength {
+        expected: usize,
+        found: usize,
+    },
+    UnknownCard(char),
+    MissingWager,
+    /// The wager is not a non-negative integer
+    InvalidWager(String),
+    /// Anything following the wager
+    UnexpectedToken(String),
+}
 
-        let joker_count = *char_map.get(&1).unwrap_or(&0);
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
 
-        if joker_count == 5 {
-            return HandValue::FiveOfKind;
+        match &self.kind {
+            ParseErrorKind::MissingCards => write!(f, "expected a hand"),
+            ParseErrorKind::WrongHandLength { expected, found } => {
+                write!(f, "expected {expected} cards, found {found}")
+            }
+            ParseErrorKind::UnknownCard(c) => write!(f, "unknown card {c:?}"),
+            ParseErrorKind::MissingWager => write!(f, "expected a wager"),
+            ParseErrorKind::InvalidWager(s) => write!(f, "invalid wager {s:?}"),
+            ParseErrorKind::UnexpectedToken(s) => write!(f, "unexpected {s:?} after the wager"),
         }
+    }
+}
 
-        let mode = char_map
-            .iter()
-            .filter(|(&k, _)| k != 1)
-            .max_by(|(_, v1), (_, v2)| v1.cmp(v2))
-            .unwrap();
+impl Error for ParseError {}
 
-        char_map
-            .entry(*mode.0)
-            .and_modify(|count| *count += joker_count);
+/// The cards of a hand as seen by a [`Category`], with wild cards already substituted
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HandShape<'a> {
+    /// Card ranks (positions in [`RuleSet::card_order`]) sorted from weakest to strongest
+    pub ranks: &'a [u8],
+    /// Sizes of each group of equal cards, largest first
+    pub groups: &'a [u8],
+}
 
-        let mut last_found = 0;
+impl HandShape<'_> {
+    /// Size of the `n`th largest group of equal cards, or 0 if there are fewer groups
+    #[must_use]
+    pub fn group(&self, n: usize) -> u8 {
+        self.groups.get(n).copied().unwrap_or(0)
+    }
+}
+
+/// A named kind of hand, such as a full house
+#[derive(Clone, Copy, Debug)]
+pub struct Category {
+    pub name: &'static str,
+    pub matches: fn(&HandShape) -> bool,
+}
+
+/// The categories from the puzzle, weakest first
+pub const STANDARD_CATEGORIES: [Category; 7] = [
+    Category {
+        name: "High card",
+        matches: |_| true,
+    },
+    Category {
+        name: "One pair",
+        matches: |shape| shape.group(0) >= 2,
+    },
+    Category {
+        name: "Two pair",
+        matches: |shape| shape.group(0) >= 2 && shape.group(1) >= 2,
+    },
+    Category {
+        name: "Three of a kind",
+        matches: |shape| shape.group(0) >= 3,
+    },
+    Category {
+        name: "Full house",
+        matches: |shape| shape.group(0) >= 3 && shape.group(1) >= 2,
+    },
+    Category {
+        name: "Four of a kind",
+        matches: |shape| shape.group(0) >= 4,
+    },
+    Category {
+        name: "Five of a kind",
+        matches: |shape| shape.group(0) >= 5,
+    },
+];
+
+/// Everything needed to rank a game of Camel Cards
+#[derive(Clone, Debug)]
+pub struct RuleSet {
+    /// Card labels from weakest to strongest, which is also how ties are broken
+    pub card_order: Vec<char>,
+    /// Cards that stand in for whichever card gives the strongest category
+    pub wild: Vec<char>,
+    pub hand_length: usize,
+    /// Hand categories from weakest to strongest. A hand takes the strongest category it
+    /// matches, so the first category should match every hand.
+    pub categories: Vec<Category>,
+}
+
+impl RuleSet {
+    /// The rules for part 1
+    #[must_use]
+    pub fn standard() -> Self {
+        Self {
+            card_order: "23456789TJQKA".chars().collect(),
+            wild: Vec::new(),
+            hand_length: 5,
+            categories: STANDARD_CATEGORIES.to_vec(),
+        }
+    }
 
-        for v in char_map
+    /// The rules for part 2, where `'J'` is a wild joker and the weakest card
+    #[must_use]
+    pub fn jokers() -> Self {
+        Self {
+            card_order: "J23456789TQKA".chars().collect(),
+            wild: vec!['J'],
+            ..Self::standard()
+        }
+    }
+
+    /// Returns the index in [`Self::categories`] of the strongest category the hand can make,
+    /// along with the cards that make it
+    fn classify(&self, cards: &[u8]) -> (usize, Vec<u8>) {
+        let mut buffers = (
+            Vec::with_capacity(cards.len()),
+            Vec::with_capacity(cards.len()),
+        );
+
+        if !cards.iter().any(|&rank| self.is_wild(rank)) {
+            return (self.best_category(cards, &mut buffers), cards.to_vec());
+        }
+
+        if self.has_standard_categories() {
+            self.classify_grouped(cards, &mut buffers)
+        } else {
+            self.classify_exhaustive(cards, &mut buffers)
+        }
+    }
+
+    /// The standard categories only ever improve when a group grows, so every wild card joins
+    /// the largest group, preferring the strongest rank on ties
+    fn classify_grouped(&self, cards: &[u8], buffers: &mut Buffers) -> (usize, Vec<u8>) {
+        let mut counts = [0u8; COUNTS_LEN];
+
+        for &rank in cards.iter().filter(|&&rank| !self.is_wild(rank)) {
+            counts[usize::from(rank)] += 1;
+        }
+
+        // With only wild cards in the hand, the strongest card that is not wild
+        let target = (0..self.card_order.len().min(COUNTS_LEN))
+            .filter_map(|rank| u8::try_from(rank).ok())
+            .filter(|&rank| !self.is_wild(rank))
+            .max_by_key(|&rank| (counts[usize::from(rank)], rank));
+
+        let Some(target) = target else {
+            return (self.best_category(cards, buffers), cards.to_vec());
+        };
+
+        let resolved: Vec<u8> = cards
             .iter()
-            .filter_map(|(k, v)| if *k == 1 { None } else { Some(v) })
-        {
-            match v {
-                5 => {
-                    return HandValue::FiveOfKind;
-                }
-                4 => {
-                    return HandValue::FourOfKind;
-                }
-                3 => {
-                    if last_found == 2 {
-                        return HandValue::FullHouse;
-                    }
+            .map(|&rank| if self.is_wild(rank) { target } else { rank })
+            .collect();
 
-                    last_found = 3;
-                }
-                2 => {
-                    if last_found == 3 {
-                        return HandValue::FullHouse;
-                    }
+        (self.best_category(&resolved, buffers), resolved)
+    }
 
-                    if last_found == 2 {
-                        return HandValue::TwoPair;
-                    }
+    /// Tries every substitution for the wild cards, for categories that may not follow the
+    /// standard rules
+    fn classify_exhaustive(&self, cards: &[u8], buffers: &mut Buffers) -> (usize, Vec<u8>) {
+        let wild_positions: Vec<usize> = (0..cards.len())
+            .filter(|&i| self.is_wild(cards[i]))
+            .collect();
 
-                    last_found = 2;
+        let substitutes: Vec<u8> = (0..self.card_order.len())
+            .filter_map(|rank| u8::try_from(rank).ok())
+            .filter(|&rank| !self.is_wild(rank))
+            .collect();
+
+        if substitutes.is_empty() {
+            return (self.best_category(cards, buffers), cards.to_vec());
+        }
+
+        // Categories only see the sorted ranks, so trying every multiset of substitutes is
+        // enough; the choices are kept non-decreasing to skip permutations
+        let mut resolved = cards.to_vec();
+        let mut choices = vec![0; wild_positions.len()];
+        let mut best: Option<(usize, Vec<u8>)> = None;
+
+        loop {
+            for (&position, &choice) in wild_positions.iter().zip(&choices) {
+                resolved[position] = substitutes[choice];
+            }
+
+            let value = self.best_category(&resolved, buffers);
+
+            // The first substitution is kept even if nothing beats it, so that the wild cards
+            // are always played as something
+            match &mut best {
+                Some((best_value, best_cards)) if value > *best_value => {
+                    *best_value = value;
+                    best_cards.copy_from_slice(&resolved);
                 }
-                _ => {}
+                Some(_) => {}
+                None => best = Some((value, resolved.clone())),
             }
-        }
 
-        if last_found == 3 {
-            return HandValue::ThreeOfKind;
+            let Some(i) = (0..choices.len()).rfind(|&i| choices[i] + 1 < substitutes.len()) else {
+                break;
+            };
+
+            choices[i] += 1;
+            let next = choices[i];
+            choices[i + 1..].fill(next);
         }
 
-        if last_found == 2 {
-            return HandValue::OnePair;
+        best.unwrap_or_else(|| (self.best_category(cards, buffers), cards.to_vec()))
+    }
+
+    /// Uses `buffers` for the sorted ranks and group sizes so that repeated calls do not
+    /// allocate
+    fn best_category(&self, cards: &[u8], (ranks, groups): &mut Buffers) -> usize {
+        ranks.clear();
+        ranks.extend_from_slice(cards);
+        ranks.sort_unstable();
+
+        let mut counts = [0u8; COUNTS_LEN];
+
+        for &rank in ranks.iter() {
+            counts[usize::from(rank)] += 1;
         }
 
-        HandValue::HighCard
+        groups.clear();
+        groups.extend(counts.iter().copied().filter(|&count| count > 0));
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        let shape = HandShape { ranks, groups };
+
+        self.categories
+            .iter()
+            .rposition(|category| (category.matches)(&shape))
+            .unwrap_or(0)
+    }
+
+    /// Whether the categories are [`STANDARD_CATEGORIES`], both by name and by what they
+    /// match
+    ///
+    /// Function pointers to the same function can compare unequal across codegen units, but that
+    /// only costs [`Self::classify_exhaustive`] instead of the shortcut.
+    fn has_standard_categories(&self) -> bool {
+        self.categories.len() == STANDARD_CATEGORIES.len()
+            && self
+                .categories
+                .iter()
+                .zip(&STANDARD_CATEGORIES)
+                .all(|(category, standard)| {
+                    category.name == standard.name
+                        && std::ptr::fn_addr_eq(category.matches, standard.matches)
+                })
+    }
+
+    fn card_labels(&self, cards: &[u8]) -> String {
+        cards
+            .iter()
+            .map(|&rank| self.card_order[usize::from(rank)])
+            .collect()
+    }
+
+    fn is_wild(&self, rank: u8) -> bool {
+        self.wild.contains(&self.card_order[usize::from(rank)])
+    }
+
+    /// On error, also returns the 0-based offset of the offending card within `card_str`
+    fn parse_cards(&self, card_str: &str) -> Result<Vec<u8>, (usize, ParseErrorKind)> {
+        let cards = card_str
+            .chars()
+            .enumerate()
+            .map(|(offset, c)| {
+                self.card_order
+                    .iter()
+                    .position(|&card| card == c)
+                    .and_then(|rank| u8::try_from(rank).ok())
+                    .ok_or((offset, ParseErrorKind::UnknownCard(c)))
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        if cards.len() == self.hand_length {
+            Ok(cards)
+        } else {
+            Err((
+                0,
+                ParseErrorKind::WrongHandLength {
+                    expected: self.hand_length,
+                    found: cards.len(),
+                },
+            ))
+        }
     }
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let self_value = self.get_value();
-        let other_value = other.get_value();
-        let cmp = self_value.cmp(&other_value);
+/// Ranks are stored as `u8`, so this many counters cover every card
+const COUNTS_LEN: usize = 1 << u8::BITS;
+
+/// Reusable space for a hand's sorted ranks and group sizes
+type Buffers = (Vec<u8>, Vec<u8>);
 
-        if cmp == Ordering::Equal {
-            // tie-breaker
-            return self.cards.cmp(&other.cards);
+#[derive(Debug, Eq, PartialEq)]
+struct Hand {
+    cards: Vec<u8>,
+    wager: i64,
+    /// Index of the hand's category in the [`RuleSet`] it was parsed with
+    value: usize,
+    /// The cards with wild cards substituted to make the category
+    resolved: Vec<u8>,
+}
+
+impl Hand {
+    fn new(rules: &RuleSet, cards: Vec<u8>, wager: i64) -> Self {
+        let (value, resolved) = rules.classify(&cards);
+
+        Self {
+            cards,
+            wager,
+            value,
+            resolved,
         }
+    }
+}
 
-        cmp
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.value, &self.cards).cmp(&(other.value, &other.cards))
     }
 }
 
@@ -137,36 +470,76 @@ impl PartialOrd for Hand {
     }
 }
 
-fn parse_input(input: &[String], part2: bool) -> Vec<Hand> {
+fn parse_input(input: &[String], rules: &RuleSet) -> Result<Vec<Hand>, ParseError> {
     input
         .iter()
-        .map(|s| s.split_whitespace().take(2))
-        .map(|mut split| {
-            let card_str = split.next().unwrap();
-            let wager_str = split.next().unwrap();
-            let mut chars = card_str.chars();
-
-            Hand {
-                cards: [(); 5].map(|()| match chars.next().unwrap() {
-                    'A' => 14,
-                    'K' => 13,
-                    'Q' => 12,
-                    'J' => {
-                        if part2 {
-                            1
-                        } else {
-                            11
-                        }
-                    }
-                    'T' => 10,
-                    c => u8::try_from(c.to_digit(10).unwrap()).unwrap(),
-                }),
-                wager: wager_str.parse().unwrap(),
+        .enumerate()
+        .map(|(index, line)| {
+            let to_error = |column, kind| ParseError {
+                line: index + 1,
+                column,
+                kind,
+            };
+
+            let mut tokens = tokens(line);
+
+            let (card_column, card_str) = tokens
+                .next()
+                .ok_or_else(|| to_error(1, ParseErrorKind::MissingCards))?;
+
+            let cards = rules
+                .parse_cards(card_str)
+                .map_err(|(offset, kind)| to_error(card_column + offset, kind))?;
+
+            let (wager_column, wager_str) = tokens
+                .next()
+                .ok_or_else(|| to_error(line.chars().count() + 1, ParseErrorKind::MissingWager))?;
+
+            let wager = wager_str.parse::<u32>().map_err(|_| {
+                to_error(
+                    wager_column,
+                    ParseErrorKind::InvalidWager(wager_str.to_owned()),
+                )
+            })?;
+
+            if let Some((column, token)) = tokens.next() {
+                return Err(to_error(
+                    column,
+                    ParseErrorKind::UnexpectedToken(token.to_owned()),
+                ));
             }
+
+            Ok(Hand::new(rules, cards, i64::from(wager)))
         })
         .collect()
 }
 
+/// Splits a line on whitespace, pairing each token with its 1-based character column
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut column = 0;
+    let mut start = None;
+    let mut bounds = Vec::new();
+
+    for (byte, ch) in line.char_indices() {
+        column += 1;
+
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some((column, byte)),
+            (true, Some((token_column, token_start))) => {
+                bounds.push((token_column, &line[token_start..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((token_column, token_start)) = start {
+        bounds.push((token_column, &line[token_start..]));
+    }
+
+    bounds.into_iter()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,33 +556,24 @@ mod tests {
     }
 
     fn get_test_output() -> Vec<Hand> {
+        let rules = RuleSet::standard();
+
         vec![
-            Hand {
-                cards: [3, 2, 10, 3, 13],
-                wager: 765,
-            },
-            Hand {
-                cards: [10, 5, 5, 11, 5],
-                wager: 684,
-            },
-            Hand {
-                cards: [13, 13, 6, 7, 7],
-                wager: 28,
-            },
-            Hand {
-                cards: [13, 10, 11, 11, 10],
-                wager: 220,
-            },
-            Hand {
-                cards: [12, 12, 12, 11, 14],
-                wager: 483,
-            },
+            Hand::new(&rules, vec![1, 0, 8, 1, 11], 765),
+            Hand::new(&rules, vec![8, 3, 3, 9, 3], 684),
+            Hand::new(&rules, vec![11, 11, 4, 5, 5], 28),
+            Hand::new(&rules, vec![11, 8, 9, 9, 8], 220),
+            Hand::new(&rules, vec![10, 10, 10, 9, 12], 483),
         ]
     }
 
+    fn category_name(rules: &RuleSet, card_str: &str) -> &'static str {
+        rules.categories[rules.classify(&rules.parse_cards(card_str).unwrap()).0].name
+    }
+
     #[test]
     fn parse_input_test() {
-        let result = parse_input(&get_test_input(), false);
+        let result = parse_input(&get_test_input(), &RuleSet::standard()).unwrap();
         let expected_result = get_test_output();
 
         assert_eq!(result, expected_result);
@@ -217,13 +581,173 @@ mod tests {
 
     #[test]
     fn hand_value_test() {
-        let test_hand = Hand {
-            cards: [9, 2, 12, 14, 1],
-            wager: 1,
+        let rules = RuleSet::jokers();
+
+        assert_eq!(category_name(&rules, "92QAJ"), "One pair");
+        assert_eq!(category_name(&rules, "JJJJJ"), "Five of a kind");
+        assert_eq!(category_name(&rules, "2233J"), "Full house");
+        assert_eq!(category_name(&rules, "22334"), "Two pair");
+        assert_eq!(category_name(&rules, "2J3J4"), "Three of a kind");
+        assert_eq!(category_name(&rules, "23456"), "High card");
+    }
+
+    #[test]
+    fn ranking_report_test() {
+        let report = ranking_report(&RuleSet::jokers(), &get_test_input()).unwrap();
+
+        assert_eq!(
+            report[0],
+            RankedHand {
+                cards: "32T3K".to_owned(),
+                resolved: "32T3K".to_owned(),
+                category: "One pair",
+                rank: 1,
+                wager: 765,
+                winnings: 765,
+            }
+        );
+
+        assert_eq!(report[4].cards, "KTJJT");
+        assert_eq!(report[4].resolved, "KTTTT");
+        assert_eq!(report[4].category, "Four of a kind");
+        assert_eq!(report[4].winnings, 220 * 5);
+
+        let table = format_report(&report[0..1]);
+
+        assert_eq!(
+            table,
+            " Rank  Cards  Played as  Category   Wager    Winnings\n    1  32T3K  32T3K      One pair     765         765\n"
+        );
+    }
+
+    #[test]
+    fn classify_grouped_test() {
+        let rules = RuleSet::jokers();
+        let mut buffers = Buffers::default();
+        let cards: Vec<&str> = vec![
+            "JJJJJ", "J2345", "JJ233", "2J3J4", "KTJJT", "QQJKK", "JAJAJ",
+        ];
+
+        for card_str in cards {
+            let cards = rules.parse_cards(card_str).unwrap();
+
+            assert_eq!(
+                rules.classify_grouped(&cards, &mut buffers).0,
+                rules.classify_exhaustive(&cards, &mut buffers).0,
+                "{card_str}"
+            );
+        }
+
+        let cards = rules.parse_cards("JJJJJ").unwrap();
+
+        assert_eq!(rules.card_labels(&rules.classify(&cards).1), "AAAAA");
+    }
+
+    #[test]
+    fn renamed_categories_test() {
+        let mut rules = RuleSet::jokers();
+
+        assert!(rules.has_standard_categories());
+
+        // A straight that borrows the name of two pair can only be made by trying every card
+        rules.categories[2].matches = |shape| {
+            shape.groups.iter().all(|&size| size == 1)
+                && shape.ranks.windows(2).all(|pair| pair[0] + 1 == pair[1])
+        };
+
+        assert!(!rules.has_standard_categories());
+        assert_eq!(category_name(&rules, "2J456"), "Two pair");
+    }
+
+    #[test]
+    fn custom_rule_set_test() {
+        let mut categories = STANDARD_CATEGORIES[0..4].to_vec();
+        categories.push(Category {
+            name: "Straight",
+            matches: |shape| shape.ranks.windows(2).all(|pair| pair[1] == pair[0] + 1),
+        });
+
+        let rules = RuleSet {
+            card_order: "123456".chars().collect(),
+            wild: vec!['1'],
+            hand_length: 3,
+            categories,
         };
-        let result = test_hand.get_value();
 
-        assert_eq!(result, HandValue::OnePair);
+        assert_eq!(category_name(&rules, "234"), "Straight");
+        assert_eq!(category_name(&rules, "216"), "One pair");
+        assert_eq!(category_name(&rules, "513"), "Straight");
+        assert_eq!(category_name(&rules, "111"), "Straight");
+
+        let input = [
+            "234 1".to_owned(),
+            "256 10".to_owned(),
+            "663 100".to_owned(),
+        ];
+
+        assert_eq!(total_winnings(&rules, &input), Ok(3 + 10 + 200));
+    }
+
+    #[test]
+    fn wild_only_report_test() {
+        // Nothing beats high card, but the wild cards are still played as something
+        let rules = RuleSet {
+            card_order: "123".chars().collect(),
+            wild: vec!['1'],
+            hand_length: 3,
+            categories: STANDARD_CATEGORIES[0..1].to_vec(),
+        };
+
+        let report = ranking_report(&rules, &["111 5".to_owned()]).unwrap();
+
+        assert_eq!(report[0].resolved, "222");
+        assert_eq!(report[0].category, "High card");
+        assert!(format_report(&report).contains("  111    222  "));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let rules = RuleSet::jokers();
+        let parse_line = |line: &str| {
+            parse_input(&[line.to_owned()], &rules).map_err(|err| (err.column, err.kind))
+        };
+
+        assert_eq!(
+            parse_line("32T3 765"),
+            Err((
+                1,
+                ParseErrorKind::WrongHandLength {
+                    expected: 5,
+                    found: 4,
+                },
+            ))
+        );
+        assert_eq!(
+            parse_line("  32t3K 765"),
+            Err((5, ParseErrorKind::UnknownCard('t')))
+        );
+        assert_eq!(
+            parse_line("3213K 765"),
+            Err((3, ParseErrorKind::UnknownCard('1')))
+        );
+        assert_eq!(parse_line("32T3K"), Err((6, ParseErrorKind::MissingWager)));
+        assert_eq!(
+            parse_line("32T3K -765"),
+            Err((7, ParseErrorKind::InvalidWager("-765".to_owned())))
+        );
+        assert_eq!(
+            parse_line("32T3K 765 28"),
+            Err((11, ParseErrorKind::UnexpectedToken("28".to_owned())))
+        );
+        assert_eq!(parse_line(""), Err((1, ParseErrorKind::MissingCards)));
+
+        let input = ["32T3K 765".to_owned(), "KK6 28".to_owned()];
+        let result = total_winnings(&rules, &input);
+
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("line 2, column 1: expected 5 cards, found 3".to_owned())
+        );
     }
 
     #[test]
@@ -243,12 +767,9 @@ mod tests {
     #[test]
     fn part2_xxx_test() {
         let input = inputs::get_input::<String>("data/day_7.txt").expect("Could not parse path!");
-        let hands = parse_input(&input, true);
-
-        for hand in hands {
-            let _value = hand.get_value();
-        }
+        let hands = parse_input(&input, &RuleSet::jokers()).unwrap();
 
+        assert_eq!(hands.len(), 1_000);
         assert_eq!(input.len(), 1_000);
     }
 }
@@ -1,40 +1,271 @@
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Display, Write};
 
+/// # Panics
+///
+/// Panics if the network is invalid or `ZZZ` can not be reached, see [`try_part1`]
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
-    const TERM_NODE: Node = Node::new(*b"ZZZ");
-
-    let network = parse_input(input);
-
-    count_steps(&network, Node::new(*b"AAA"), |node| node == TERM_NODE)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics if the network is invalid or the ghosts never arrive together, see [`try_part2`]
 #[must_use]
 pub fn part2(input: &[String]) -> i64 {
-    let network = parse_input(input);
+    try_part2(input).unwrap()
+}
+
+/// Counts the steps from `AAA` to `ZZZ`
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The network is malformed or uses an undefined node
+/// * `AAA` is not defined
+/// * `ZZZ` can not be reached from `AAA`
+pub fn try_part1(input: &[String]) -> Result<i64, NetworkError> {
+    let start = NodePattern::Exact("AAA".to_owned());
+    let end = NodePattern::Exact("ZZZ".to_owned());
+
+    Ok(navigate(input, &start, &end)?.steps[0].1)
+}
+
+/// Counts the steps until every ghost starting on a node ending in `A` is on a node ending
+/// in `Z` at the same time
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The network is malformed or uses an undefined node
+/// * A ghost can never reach an end node
+/// * The ghosts are never all on an end node at the same time
+pub fn try_part2(input: &[String]) -> Result<i64, NetworkError> {
+    let start = NodePattern::Suffix("A".to_owned());
+    let end = NodePattern::Suffix("Z".to_owned());
+
+    navigate(input, &start, &end)?
+        .together()
+        .ok_or(NetworkError::NoSimultaneousArrival)
+}
+
+/// Selects nodes by name
+pub enum NodePattern {
+    Exact(String),
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
+    Custom(Box<dyn Fn(&str) -> bool>),
+}
+
+impl NodePattern {
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Exact(s) => name == s,
+            Self::Prefix(s) => name.starts_with(s.as_str()),
+            Self::Suffix(s) => name.ends_with(s.as_str()),
+            Self::Regex(re) => re.is_match(name),
+            Self::Custom(pred) => pred(name),
+        }
+    }
+}
 
-    let counts: Vec<i64> = network
+impl fmt::Debug for NodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(s) => f.debug_tuple("Exact").field(s).finish(),
+            Self::Prefix(s) => f.debug_tuple("Prefix").field(s).finish(),
+            Self::Suffix(s) => f.debug_tuple("Suffix").field(s).finish(),
+            Self::Regex(re) => f.debug_tuple("Regex").field(re).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// The result of walking from every start node
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Navigation {
+    /// Each start node's name and the steps it takes to first reach an end node
+    pub steps: Vec<(String, i64)>,
+    /// Every step at which each walk is on an end node, in the same order as `steps`
+    arrivals: Vec<Arrivals>,
+}
+
+impl Navigation {
+    /// Returns the first step at which every walk is on an end node at the same time, if there
+    /// is one
+    ///
+    /// This is only worked out when asked for, as walks that arrive at many steps of their cycle
+    /// can leave a lot of steps to check.
+    #[must_use]
+    pub fn together(&self) -> Option<i64> {
+        simultaneous_arrival(&self.arrivals)
+    }
+}
+
+/// Walks from every node matching `start` until reaching a node matching `end`
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The network is malformed or uses an undefined node
+/// * No node matches `start`
+/// * A start node can never reach an end node
+pub fn navigate(
+    input: &[String],
+    start: &NodePattern,
+    end: &NodePattern,
+) -> Result<Navigation, NetworkError> {
+    let network = parse_input(input)?;
+    let is_end = network.nodes.matching(end);
+
+    let starts: Vec<Node> = network
         .nodes
-        .map
-        .keys()
-        .filter(|node| node.id[2] == b'A')
-        .map(|node| count_steps(&network, *node, |n| n.id[2] == b'Z'))
+        .nodes()
+        .filter(|&node| start.matches(network.nodes.name(node)))
         .collect();
 
-    lcm(&counts)
+    if starts.is_empty() {
+        return Err(NetworkError::NoStartNodes);
+    }
+
+    let walks: Vec<Walk> = starts
+        .iter()
+        .map(|&node| walk(&network, node, &is_end))
+        .collect();
+
+    let steps = starts
+        .iter()
+        .zip(&walks)
+        .map(|(&node, walk)| {
+            count_steps(&network, node, walk)
+                .map(|count| (network.nodes.name(node).to_owned(), count))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Navigation {
+        steps,
+        arrivals: walks.iter().map(Walk::arrivals).collect(),
+    })
 }
 
-fn count_steps(network: &Network, start: Node, pred: fn(Node) -> bool) -> i64 {
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NetworkError {
+    NoInstructions,
+    NoStartNodes,
+    /// 1-based line that is not of the form `<node> = (<left>, <right>)`
+    Malformed(usize),
+    DuplicateNode(String),
+    /// A node is used without being defined
+    UndefinedNode(String),
+    /// Walking from the node revisits a state without reaching a target
+    Unreachable(String),
+    NoSimultaneousArrival,
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoInstructions => write!(f, "no left/right instructions"),
+            Self::NoStartNodes => write!(f, "no node matches the start pattern"),
+            Self::Malformed(line) => {
+                write!(f, "line {line}: expected `<node> = (<left>, <right>)`")
+            }
+            Self::DuplicateNode(name) => write!(f, "node {name} is defined more than once"),
+            Self::UndefinedNode(name) => write!(f, "node {name} is not defined"),
+            Self::Unreachable(name) => write!(f, "no target can be reached from node {name}"),
+            Self::NoSimultaneousArrival => write!(f, "the walks never arrive at the same time"),
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+/// Counts the steps `walk` takes from `start` to its first target
+fn count_steps(network: &Network, start: Node, walk: &Walk) -> Result<i64, NetworkError> {
+    walk.first_arrival()
+        .ok_or_else(|| NetworkError::Unreachable(network.nodes.name(start).to_owned()))
+}
+
+/// A walk from one start node, up to the first repeated `(node, instruction index)` state
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Walk {
+    /// Each node left and the direction taken
+    moves: Vec<(Node, Direction)>,
+    /// Steps at which the walk is on a target
+    hits: Vec<i64>,
+    /// Step at which the repeated state was first visited
+    cycle_start: usize,
+}
+
+impl Walk {
+    fn first_arrival(&self) -> Option<i64> {
+        self.hits.first().copied()
+    }
+
+    /// The moves up to the first target, or every move if no target is reached
+    fn moves_to_target(&self) -> &[(Node, Direction)] {
+        let end = self
+            .hits
+            .first()
+            .map_or(self.moves.len(), |&hit| hit as usize);
+
+        &self.moves[..end]
+    }
+
+    fn arrivals(&self) -> Arrivals {
+        let cycle_start = self.cycle_start as i64;
+        let (pre_cycle, periodic) = self.hits.iter().partition(|&&hit| hit < cycle_start);
+
+        Arrivals {
+            pre_cycle,
+            periodic,
+            cycle_start,
+            cycle_length: (self.moves.len() - self.cycle_start) as i64,
+        }
+    }
+}
+
+/// Walks from `start` until a `(node, instruction index)` state repeats, recording the moves
+/// and the steps at which a target is reached
+fn walk(network: &Network, start: Node, is_target: &[bool]) -> Walk {
+    const UNSEEN: usize = usize::MAX;
+
+    let instruction_count = network.instructions.len();
+    let mut first_seen = vec![UNSEEN; network.nodes.names.len() * instruction_count];
+    let mut moves = Vec::new();
+    let mut hits = Vec::new();
     let mut current_node = start;
 
     for step in 0.. {
-        if pred(current_node) {
-            return step as i64;
+        let index = step % instruction_count;
+        let state = current_node.index() * instruction_count + index;
+
+        if first_seen[state] != UNSEEN {
+            return Walk {
+                moves,
+                hits,
+                cycle_start: first_seen[state],
+            };
         }
 
-        let instruction = network.get_instruction(step);
+        first_seen[state] = step;
 
-        current_node = match instruction {
+        if is_target[current_node.index()] {
+            hits.push(step as i64);
+        }
+
+        let direction = network.instructions[index];
+        moves.push((current_node, direction));
+
+        current_node = match direction {
             Direction::Left => network.nodes.left(current_node).unwrap(),
             Direction::Right => network.nodes.right(current_node).unwrap(),
         };
@@ -43,6 +274,275 @@ fn count_steps(network: &Network, start: Node, pred: fn(Node) -> bool) -> i64 {
     unreachable!()
 }
 
+/// Renders the network as a Graphviz DOT graph
+///
+/// Edges are labelled `L`/`R` and start and end nodes are highlighted. If `show_paths` is set,
+/// the walk from each start node is drawn over the edges in its own color, stopping at the
+/// first end node or once the walk starts repeating.
+///
+/// # Errors
+///
+/// This function will return an error if the network is malformed or uses an undefined node
+pub fn to_dot(
+    input: &[String],
+    start: &NodePattern,
+    end: &NodePattern,
+    show_paths: bool,
+) -> Result<String, NetworkError> {
+    const PATH_COLORS: [&str; 6] = [
+        "blue",
+        "darkorange",
+        "purple",
+        "forestgreen",
+        "red",
+        "cyan4",
+    ];
+
+    let network = parse_input(input)?;
+    let nodes = &network.nodes;
+    let starts = nodes.matching(start);
+    let ends = nodes.matching(end);
+
+    // Colors of the walks taking each edge, keyed by the node left and the direction
+    let mut walked: HashMap<(Node, Direction), Vec<&str>> = HashMap::new();
+
+    if show_paths {
+        for (start, color) in nodes
+            .nodes()
+            .filter(|node| starts[node.index()])
+            .zip(PATH_COLORS.iter().cycle())
+        {
+            for &edge in walk(&network, start, &ends).moves_to_target() {
+                let colors = walked.entry(edge).or_default();
+
+                if !colors.contains(color) {
+                    colors.push(color);
+                }
+            }
+        }
+    }
+
+    let mut output = String::from("digraph network {\n    node [shape=ellipse];\n");
+
+    for node in nodes.nodes() {
+        let fill_color = match (starts[node.index()], ends[node.index()]) {
+            (true, true) => "\"palegreen:lightcoral\"",
+            (true, false) => "palegreen",
+            (false, true) => "lightcoral",
+            (false, false) => continue,
+        };
+
+        let _ = writeln!(
+            output,
+            "    {} [style=filled, fillcolor={fill_color}];",
+            dot_id(nodes.name(node))
+        );
+    }
+
+    for node in nodes.nodes() {
+        for (direction, label, next) in [
+            (Direction::Left, "L", nodes.left(node)),
+            (Direction::Right, "R", nodes.right(node)),
+        ] {
+            let Some(next) = next else {
+                continue;
+            };
+
+            let _ = write!(
+                output,
+                "    {} -> {} [label=\"{label}\"",
+                dot_id(nodes.name(node)),
+                dot_id(nodes.name(next))
+            );
+
+            if let Some(colors) = walked.get(&(node, direction)) {
+                let _ = write!(output, ", color=\"{}\", penwidth=2", colors.join(":"));
+            }
+
+            output.push_str("];\n");
+        }
+    }
+
+    output.push_str("}\n");
+
+    Ok(output)
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Every step at which a walk from one start node satisfies the predicate
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Arrivals {
+    /// Arrivals before the walk enters its cycle, which each happen once
+    pre_cycle: Vec<i64>,
+    /// Arrivals during the first pass of the cycle, which each repeat every `cycle_length` steps
+    periodic: Vec<i64>,
+    cycle_start: i64,
+    cycle_length: i64,
+}
+
+impl Arrivals {
+    fn contains(&self, step: i64) -> bool {
+        if step < self.cycle_start {
+            self.pre_cycle.contains(&step)
+        } else {
+            let offset = (step - self.cycle_start) % self.cycle_length;
+
+            self.periodic.contains(&(self.cycle_start + offset))
+        }
+    }
+
+    /// The walk arrives exactly at multiples of the cycle length, so the answer is the LCM
+    fn is_lcm_friendly(&self) -> bool {
+        self.pre_cycle.is_empty() && self.periodic == [self.cycle_length]
+    }
+}
+
+/// How many residue classes [`simultaneous_arrival`] may merge a walk's arrivals into
+const MAX_RESIDUE_CLASSES: usize = 1 << 16;
+
+/// Finds the first step at which every walk arrives at the same time
+fn simultaneous_arrival(arrivals: &[Arrivals]) -> Option<i64> {
+    if arrivals.is_empty() {
+        return None;
+    }
+
+    if arrivals.iter().all(Arrivals::is_lcm_friendly) {
+        let cycles: Vec<i64> = arrivals.iter().map(|a| a.cycle_length).collect();
+
+        return Some(lcm(&cycles));
+    }
+
+    // Before the last walk enters its cycle, that walk can only arrive at a pre-cycle step,
+    // which comes before any step at which every walk is periodic
+    let pre_cycle_best = arrivals
+        .iter()
+        .flat_map(|a| a.pre_cycle.iter().copied())
+        .filter(|&step| arrivals.iter().all(|a| a.contains(step)))
+        .min();
+
+    if pre_cycle_best.is_some() {
+        return pre_cycle_best;
+    }
+
+    // Afterwards, every walk is periodic, so merge the residue classes of each walk's periodic
+    // arrivals, starting with the walks that arrive the least. Every class shares the same
+    // modulus after a merge, so duplicates are dropped to keep them below it.
+    let all_cyclic_from = i128::from(arrivals.iter().map(|a| a.cycle_start).max().unwrap_or(0));
+    let mut walks: Vec<&Arrivals> = arrivals.iter().collect();
+    walks.sort_by_key(|a| a.periodic.len());
+
+    let mut remainders = vec![0i128];
+    let mut modulus = 1i128;
+    let mut merged = 0;
+
+    for a in &walks {
+        // Walks that arrive too often to merge are checked against each step instead
+        if remainders.len() * a.periodic.len() > MAX_RESIDUE_CLASSES {
+            break;
+        }
+
+        let cycle_length = i128::from(a.cycle_length);
+        let mut next_modulus = modulus;
+
+        remainders = remainders
+            .iter()
+            .flat_map(|&rem| {
+                a.periodic
+                    .iter()
+                    .filter_map(move |&hit| crt(rem, modulus, i128::from(hit), cycle_length))
+            })
+            .map(|(rem, merged_modulus)| {
+                next_modulus = merged_modulus;
+                rem
+            })
+            .collect();
+
+        remainders.sort_unstable();
+        remainders.dedup();
+
+        if remainders.is_empty() {
+            return None;
+        }
+
+        modulus = next_modulus;
+        merged += 1;
+    }
+
+    let others: Vec<(i128, i128, HashSet<i128>)> = walks[merged..]
+        .iter()
+        .map(|a| {
+            let offsets = a
+                .periodic
+                .iter()
+                .map(|&hit| i128::from(hit - a.cycle_start));
+
+            (
+                i128::from(a.cycle_start),
+                i128::from(a.cycle_length),
+                offsets.collect(),
+            )
+        })
+        .collect();
+
+    // Together the walks repeat every `period` steps, so if no step before then works, none
+    // ever will
+    let period = arrivals
+        .iter()
+        .try_fold(1i128, |acc, a| {
+            let cycle_length = i128::from(a.cycle_length);
+
+            (acc / extended_gcd(acc, cycle_length).0).checked_mul(cycle_length)
+        })
+        .unwrap_or(i128::MAX);
+    let end = all_cyclic_from
+        .saturating_add(period)
+        .min(i128::from(i64::MAX));
+
+    let mut base = all_cyclic_from / modulus * modulus;
+
+    while base < end {
+        for &rem in &remainders {
+            let step = base + rem;
+
+            if step < all_cyclic_from {
+                continue;
+            }
+
+            if step >= end {
+                return None;
+            }
+
+            if others.iter().all(|(cycle_start, cycle_length, offsets)| {
+                offsets.contains(&((step - cycle_start) % cycle_length))
+            }) {
+                return i64::try_from(step).ok();
+            }
+        }
+
+        base += modulus;
+    }
+
+    None
+}
+
+/// Combines `x ≡ a1 (mod m1)` and `x ≡ a2 (mod m2)` into `x ≡ a (mod lcm(m1, m2))`, with `a`
+/// the smallest non-negative solution, or `None` if there is no solution
+fn crt(a1: i128, m1: i128, a2: i128, m2: i128) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+
+    let modulus = m1 / g * m2;
+    let k = ((a2 - a1) / g % (m2 / g)) * p % (m2 / g);
+
+    Some(((a1 + m1 * k).rem_euclid(modulus), modulus))
+}
+
 fn lcm(nums: &[i64]) -> i64 {
     if nums.len() == 1 {
         return nums[0];
@@ -61,43 +561,110 @@ fn gcd(a: i64, b: i64) -> i64 {
     gcd(b, a % b)
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (divisor, x, y) = extended_gcd(b, a % b);
+
+        (divisor, y, x - a / b * y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 enum Direction {
     Left,
     Right,
 }
 
+/// A node name interned into a dense index into its [`NodeMap`]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 struct Node {
-    id: [u8; 3],
+    id: u32,
 }
 
 impl Node {
-    const fn new(id: [u8; 3]) -> Self {
+    const fn new(id: u32) -> Self {
         Self { id }
     }
+
+    const fn index(self) -> usize {
+        self.id as usize
+    }
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
 struct NodeMap {
-    map: HashMap<Node, (Node, Node)>,
+    names: Vec<String>,
+    ids: HashMap<String, Node>,
+    /// Left and right neighbours of each node, indexed by [`Node::index`]
+    edges: Vec<(u32, u32)>,
 }
 
 impl NodeMap {
-    fn insert(&mut self, node: Node, left: Node, right: Node) {
-        self.map.insert(node, (left, right));
+    /// Marks nodes that have been referenced but not yet defined
+    const UNDEFINED: (u32, u32) = (u32::MAX, u32::MAX);
+
+    fn intern(&mut self, name: &str) -> Node {
+        if let Some(&node) = self.ids.get(name) {
+            return node;
+        }
+
+        let node = Node::new(u32::try_from(self.names.len()).unwrap());
+
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), node);
+        self.edges.push(Self::UNDEFINED);
+
+        node
+    }
+
+    fn insert_from_str(&mut self, node: &str, left: &str, right: &str) {
+        let node = self.intern(node);
+        let left = self.intern(left);
+        let right = self.intern(right);
+
+        self.edges[node.index()] = (left.id, right.id);
     }
 
-    fn insert_from_str(&mut self, node: [u8; 3], left: [u8; 3], right: [u8; 3]) {
-        self.insert(Node::new(node), Node::new(left), Node::new(right));
+    fn is_defined(&self, node: Node) -> bool {
+        self.edges[node.index()] != Self::UNDEFINED
+    }
+
+    fn get(&self, name: &str) -> Option<Node> {
+        self.ids.get(name).copied()
+    }
+
+    fn name(&self, node: Node) -> &str {
+        &self.names[node.index()]
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn nodes(&self) -> impl Iterator<Item = Node> {
+        (0..self.names.len()).map(|index| Node::new(index as u32))
+    }
+
+    /// Matches `pattern` against every node name, indexed by [`Node::index`]
+    fn matching(&self, pattern: &NodePattern) -> Vec<bool> {
+        self.names
+            .iter()
+            .map(|name| pattern.matches(name))
+            .collect()
     }
 
     fn left(&self, node: Node) -> Option<Node> {
-        self.map.get(&node).map(|x| x.0)
+        self.edges
+            .get(node.index())
+            .filter(|&&edge| edge != Self::UNDEFINED)
+            .map(|x| Node::new(x.0))
     }
 
     fn right(&self, node: Node) -> Option<Node> {
-        self.map.get(&node).map(|x| x.1)
+        self.edges
+            .get(node.index())
+            .filter(|&&edge| edge != Self::UNDEFINED)
+            .map(|x| Node::new(x.1))
     }
 }
 
@@ -107,16 +674,19 @@ struct Network {
     nodes: NodeMap,
 }
 
-impl Network {
-    fn get_instruction(&self, index: usize) -> Direction {
-        let fixed = index % self.instructions.len();
-
-        self.instructions[fixed]
-    }
-}
-
-fn parse_input(input: &[String]) -> Network {
-    let instructions = input[0]
+/// Parses the instructions and node network, checking that every node is defined exactly once
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * There are no instructions
+/// * A node line is malformed
+/// * A node is defined more than once, or used without being defined
+fn parse_input(input: &[String]) -> Result<Network, NetworkError> {
+    let instructions: Vec<Direction> = input
+        .first()
+        .ok_or(NetworkError::NoInstructions)?
         .chars()
         .filter_map(|c| match c {
             'L' => Some(Direction::Left),
@@ -125,22 +695,41 @@ fn parse_input(input: &[String]) -> Network {
         })
         .collect();
 
+    if instructions.is_empty() {
+        return Err(NetworkError::NoInstructions);
+    }
+
     let mut nodes = NodeMap::default();
 
-    input.iter().skip(2).for_each(|line| {
-        let mut letters = line.bytes().filter(u8::is_ascii_alphanumeric);
+    for (index, line) in input.iter().enumerate().skip(2) {
+        let (node, neighbours) = line
+            .split_once('=')
+            .ok_or(NetworkError::Malformed(index + 1))?;
 
-        let s1 = [(); 3].map(|()| letters.next().unwrap());
-        let s2 = [(); 3].map(|()| letters.next().unwrap());
-        let s3 = [(); 3].map(|()| letters.next().unwrap());
+        let (left, right) = neighbours
+            .trim()
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(','))
+            .ok_or(NetworkError::Malformed(index + 1))?;
 
-        nodes.insert_from_str(s1, s2, s3);
-    });
+        let node = node.trim();
 
-    Network {
+        if nodes.get(node).is_some_and(|n| nodes.is_defined(n)) {
+            return Err(NetworkError::DuplicateNode(node.to_owned()));
+        }
+
+        nodes.insert_from_str(node, left.trim(), right.trim());
+    }
+
+    if let Some(node) = nodes.nodes().find(|&node| !nodes.is_defined(node)) {
+        return Err(NetworkError::UndefinedNode(nodes.name(node).to_owned()));
+    }
+
+    Ok(Network {
         instructions,
         nodes,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -165,13 +754,13 @@ mod tests {
     fn get_test_output1_a() -> Network {
         let mut node_map = NodeMap::default();
 
-        node_map.insert_from_str(*b"AAA", *b"BBB", *b"CCC");
-        node_map.insert_from_str(*b"BBB", *b"DDD", *b"EEE");
-        node_map.insert_from_str(*b"CCC", *b"ZZZ", *b"GGG");
-        node_map.insert_from_str(*b"DDD", *b"DDD", *b"DDD");
-        node_map.insert_from_str(*b"EEE", *b"EEE", *b"EEE");
-        node_map.insert_from_str(*b"GGG", *b"GGG", *b"GGG");
-        node_map.insert_from_str(*b"ZZZ", *b"ZZZ", *b"ZZZ");
+        node_map.insert_from_str("AAA", "BBB", "CCC");
+        node_map.insert_from_str("BBB", "DDD", "EEE");
+        node_map.insert_from_str("CCC", "ZZZ", "GGG");
+        node_map.insert_from_str("DDD", "DDD", "DDD");
+        node_map.insert_from_str("EEE", "EEE", "EEE");
+        node_map.insert_from_str("GGG", "GGG", "GGG");
+        node_map.insert_from_str("ZZZ", "ZZZ", "ZZZ");
 
         Network {
             instructions: vec![Direction::Right, Direction::Left],
@@ -193,9 +782,9 @@ mod tests {
     fn get_test_output1_b() -> Network {
         let mut node_map = NodeMap::default();
 
-        node_map.insert_from_str(*b"AAA", *b"BBB", *b"BBB");
-        node_map.insert_from_str(*b"BBB", *b"AAA", *b"ZZZ");
-        node_map.insert_from_str(*b"ZZZ", *b"ZZZ", *b"ZZZ");
+        node_map.insert_from_str("AAA", "BBB", "BBB");
+        node_map.insert_from_str("BBB", "AAA", "ZZZ");
+        node_map.insert_from_str("ZZZ", "ZZZ", "ZZZ");
 
         Network {
             instructions: vec![Direction::Left, Direction::Left, Direction::Right],
@@ -222,14 +811,14 @@ mod tests {
     fn get_test_output2() -> Network {
         let mut node_map = NodeMap::default();
 
-        node_map.insert_from_str(*b"11A", *b"11B", *b"XXX");
-        node_map.insert_from_str(*b"11B", *b"XXX", *b"11Z");
-        node_map.insert_from_str(*b"11Z", *b"11B", *b"XXX");
-        node_map.insert_from_str(*b"22A", *b"22B", *b"XXX");
-        node_map.insert_from_str(*b"22B", *b"22C", *b"22C");
-        node_map.insert_from_str(*b"22C", *b"22Z", *b"22Z");
-        node_map.insert_from_str(*b"22Z", *b"22B", *b"22B");
-        node_map.insert_from_str(*b"XXX", *b"XXX", *b"XXX");
+        node_map.insert_from_str("11A", "11B", "XXX");
+        node_map.insert_from_str("11B", "XXX", "11Z");
+        node_map.insert_from_str("11Z", "11B", "XXX");
+        node_map.insert_from_str("22A", "22B", "XXX");
+        node_map.insert_from_str("22B", "22C", "22C");
+        node_map.insert_from_str("22C", "22Z", "22Z");
+        node_map.insert_from_str("22Z", "22B", "22B");
+        node_map.insert_from_str("XXX", "XXX", "XXX");
 
         Network {
             instructions: vec![Direction::Left, Direction::Right],
@@ -239,17 +828,17 @@ mod tests {
 
     #[test]
     fn parse_input_test() {
-        let result1_a = parse_input(&get_test_input1_a());
+        let result1_a = parse_input(&get_test_input1_a()).unwrap();
         let expected_result1_a = get_test_output1_a();
 
         assert_eq!(result1_a, expected_result1_a);
 
-        let result1_b = parse_input(&get_test_input1_b());
+        let result1_b = parse_input(&get_test_input1_b()).unwrap();
         let expected_result1_b = get_test_output1_b();
 
         assert_eq!(result1_b, expected_result1_b);
 
-        let result2 = parse_input(&get_test_input2());
+        let result2 = parse_input(&get_test_input2()).unwrap();
         let expected_result2 = get_test_output2();
 
         assert_eq!(result2, expected_result2);
@@ -272,4 +861,274 @@ mod tests {
 
         assert_eq!(result, 6);
     }
+
+    #[allow(clippy::manual_string_new)]
+    fn get_test_input2_offset() -> [String; 12] {
+        [
+            "L".to_owned(),
+            "".to_owned(),
+            "11A = (11B, 11B)".to_owned(),
+            "11B = (11Z, 11Z)".to_owned(),
+            "11Z = (11B, 11B)".to_owned(),
+            "22A = (22Z, 22Z)".to_owned(),
+            "22Z = (22B, 22B)".to_owned(),
+            "22B = (22C, 22C)".to_owned(),
+            "22C = (22Z, 22Z)".to_owned(),
+            "33A = (33Z, 33Z)".to_owned(),
+            "33Z = (33B, 33B)".to_owned(),
+            "33B = (33B, 33B)".to_owned(),
+        ]
+    }
+
+    #[allow(clippy::manual_string_new)]
+    fn get_test_input_long_names() -> [String; 6] {
+        [
+            "LR".to_owned(),
+            "".to_owned(),
+            "START = (MIDDLE, DEAD_END)".to_owned(),
+            "MIDDLE = (DEAD_END, FINISH)".to_owned(),
+            "FINISH = (FINISH, FINISH)".to_owned(),
+            "DEAD_END = (DEAD_END, DEAD_END)".to_owned(),
+        ]
+    }
+
+    #[test]
+    fn long_names_test() {
+        let network = parse_input(&get_test_input_long_names()).unwrap();
+        let start = network.nodes.get("START").unwrap();
+
+        assert_eq!(network.nodes.names.len(), 4);
+        assert_eq!(network.nodes.name(start), "START");
+        assert_eq!(network.nodes.right(start), network.nodes.get("DEAD_END"));
+        let finish = network
+            .nodes
+            .matching(&NodePattern::Exact("FINISH".to_owned()));
+
+        assert_eq!(
+            count_steps(&network, start, &walk(&network, start, &finish)),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn network_error_test() {
+        let network = parse_input(&get_test_input_long_names()).unwrap();
+        let dead_end = network.nodes.get("DEAD_END").unwrap();
+        let finish = network
+            .nodes
+            .matching(&NodePattern::Exact("FINISH".to_owned()));
+
+        assert_eq!(
+            count_steps(&network, dead_end, &walk(&network, dead_end, &finish)),
+            Err(NetworkError::Unreachable("DEAD_END".to_owned()))
+        );
+
+        let mut input = get_test_input_long_names().to_vec();
+        input.pop();
+
+        assert_eq!(
+            parse_input(&input),
+            Err(NetworkError::UndefinedNode("DEAD_END".to_owned()))
+        );
+
+        input.push("FINISH = (START, START)".to_owned());
+
+        assert_eq!(
+            parse_input(&input),
+            Err(NetworkError::DuplicateNode("FINISH".to_owned()))
+        );
+
+        input[2] = "START = MIDDLE, DEAD_END".to_owned();
+
+        assert_eq!(parse_input(&input), Err(NetworkError::Malformed(3)));
+
+        input[0].clear();
+
+        assert_eq!(parse_input(&input), Err(NetworkError::NoInstructions));
+        assert_eq!(
+            try_part2(&get_test_input2_offset()[..8]),
+            Err(NetworkError::UndefinedNode("22C".to_owned()))
+        );
+    }
+
+    #[test]
+    fn to_dot_test() {
+        let result = to_dot(
+            &get_test_input1_b(),
+            &NodePattern::Exact("AAA".to_owned()),
+            &NodePattern::Exact("ZZZ".to_owned()),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "digraph network {
+    node [shape=ellipse];
+    \"AAA\" [style=filled, fillcolor=palegreen];
+    \"ZZZ\" [style=filled, fillcolor=lightcoral];
+    \"AAA\" -> \"BBB\" [label=\"L\", color=\"blue\", penwidth=2];
+    \"AAA\" -> \"BBB\" [label=\"R\", color=\"blue\", penwidth=2];
+    \"BBB\" -> \"AAA\" [label=\"L\", color=\"blue\", penwidth=2];
+    \"BBB\" -> \"ZZZ\" [label=\"R\", color=\"blue\", penwidth=2];
+    \"ZZZ\" -> \"ZZZ\" [label=\"L\"];
+    \"ZZZ\" -> \"ZZZ\" [label=\"R\"];
+}
+"
+        );
+
+        assert_eq!(dot_id(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn navigate_test() {
+        let input = get_test_input2_offset();
+        let starts = NodePattern::Regex(Regex::new("^[12].A$").unwrap());
+        let ends = NodePattern::Custom(Box::new(|name| name.ends_with('Z')));
+
+        let navigation = navigate(&input, &starts, &ends).unwrap();
+
+        assert_eq!(
+            navigation.steps,
+            vec![("11A".to_owned(), 2), ("22A".to_owned(), 1)]
+        );
+        assert_eq!(navigation.together(), Some(4));
+
+        let starts = NodePattern::Exact("33A".to_owned());
+
+        let navigation = navigate(&input, &starts, &ends).unwrap();
+
+        assert_eq!(navigation.steps, vec![("33A".to_owned(), 1)]);
+        assert_eq!(navigation.together(), Some(1));
+
+        // 33B loops on itself without ever reaching an end node
+        let starts = NodePattern::Prefix("33".to_owned());
+
+        assert_eq!(
+            navigate(&input, &starts, &ends),
+            Err(NetworkError::Unreachable("33B".to_owned()))
+        );
+
+        let starts = NodePattern::Exact("44A".to_owned());
+
+        assert_eq!(
+            navigate(&input, &starts, &ends),
+            Err(NetworkError::NoStartNodes)
+        );
+    }
+
+    #[test]
+    fn walk_test() {
+        let network = parse_input(&get_test_input2_offset()).unwrap();
+        let start = |name| network.nodes.get(name).unwrap();
+        let is_end = &network.nodes.matching(&NodePattern::Suffix("Z".to_owned()));
+
+        assert_eq!(
+            walk(&network, start("22A"), is_end).arrivals(),
+            Arrivals {
+                pre_cycle: vec![],
+                periodic: vec![1],
+                cycle_start: 1,
+                cycle_length: 3,
+            }
+        );
+        assert_eq!(
+            walk(&network, start("33A"), is_end).arrivals(),
+            Arrivals {
+                pre_cycle: vec![1],
+                periodic: vec![],
+                cycle_start: 2,
+                cycle_length: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn simultaneous_arrival_test() {
+        let network = parse_input(&get_test_input2_offset()).unwrap();
+        let arrivals = |starts: &[&str]| -> Vec<Arrivals> {
+            starts
+                .iter()
+                .map(|&name| {
+                    let start = network.nodes.get(name).unwrap();
+                    let is_end = network.nodes.matching(&NodePattern::Suffix("Z".to_owned()));
+                    walk(&network, start, &is_end).arrivals()
+                })
+                .collect()
+        };
+
+        // 11A arrives every 2 steps from step 2, 22A every 3 steps from step 1
+        assert_eq!(simultaneous_arrival(&arrivals(&["11A", "22A"])), Some(4));
+        // 33A only arrives once, at step 1
+        assert_eq!(simultaneous_arrival(&arrivals(&["22A", "33A"])), Some(1));
+        assert_eq!(simultaneous_arrival(&arrivals(&["11A", "33A"])), None);
+    }
+
+    #[test]
+    fn simultaneous_arrival_many_hits_test() {
+        let periodic = |cycle_start, cycle_length, periodic: &[i64]| Arrivals {
+            pre_cycle: vec![],
+            periodic: periodic.to_vec(),
+            cycle_start,
+            cycle_length,
+        };
+
+        // Step 6 is 1 mod 5, 6 mod 7 and 0 mod 2
+        let arrivals = [
+            periodic(0, 5, &[1, 3]),
+            periodic(0, 7, &[2, 5, 6]),
+            periodic(0, 2, &[0]),
+        ];
+
+        assert_eq!(simultaneous_arrival(&arrivals), Some(6));
+
+        // Odd steps that are multiples of 7 are never multiples of 14
+        let arrivals = [periodic(1, 2, &[1]), periodic(0, 14, &[0])];
+
+        assert_eq!(simultaneous_arrival(&arrivals), None);
+
+        // Arriving one step before every multiple of the cycle length is not LCM friendly
+        let before_multiples = |cycles: &[i64]| -> Vec<Arrivals> {
+            cycles
+                .iter()
+                .map(|&cycle_length| periodic(0, cycle_length, &[cycle_length - 1]))
+                .collect()
+        };
+
+        assert_eq!(
+            simultaneous_arrival(&before_multiples(&[1009, 1013, 1019, 1021])),
+            Some(1009 * 1013 * 1019 * 1021 - 1)
+        );
+        assert_eq!(
+            simultaneous_arrival(&before_multiples(&[10_007, 10_009, 10_037, 10_039])),
+            Some(10_007 * 10_009 * 10_037 * 10_039 - 1)
+        );
+
+        // The first such step does not fit in an `i64`
+        assert_eq!(
+            simultaneous_arrival(&before_multiples(&[
+                10_007, 10_009, 10_037, 10_039, 10_061, 10_067
+            ])),
+            None
+        );
+
+        // Walks that arrive at every step once in their cycle agree straight away, however many
+        // of them there are
+        let arrivals: Vec<Arrivals> = [10_007, 10_009, 10_037, 10_039, 10_061, 10_067]
+            .iter()
+            .map(|&cycle_length| {
+                periodic(3, cycle_length, &(3..3 + cycle_length).collect::<Vec<_>>())
+            })
+            .collect();
+
+        assert_eq!(simultaneous_arrival(&arrivals), Some(3));
+    }
+
+    #[test]
+    fn crt_test() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(0, 2, 1, 3), Some((4, 6)));
+        assert_eq!(crt(1, 4, 2, 6), None);
+        assert_eq!(crt(3, 4, 1, 6), Some((7, 12)));
+    }
 }
@@ -1,15 +1,207 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
 #[must_use]
 pub fn part1(input: &[String]) -> i64 {
     let report = parse_input(input);
-    report.iter().map(predict_next).sum()
+    report
+        .iter()
+        .map(|list| predict(list, list.len() as i64, predict_next))
+        .sum()
 }
 
+#[must_use]
 pub fn part2(input: &[String]) -> i64 {
     let report = parse_input(input);
-    report.iter().map(predict_prev).sum()
+    report
+        .iter()
+        .map(|list| predict(list, -1, predict_prev))
+        .sum()
+}
+
+/// [`part1`] using [`predict_next`] instead of the closed form
+#[must_use]
+pub fn part1_recursive(input: &[String]) -> i64 {
+    parse_input(input)
+        .iter()
+        .map(|list| predict_next(list))
+        .sum()
+}
+
+/// [`part2`] using [`predict_prev`] instead of the closed form
+#[must_use]
+pub fn part2_recursive(input: &[String]) -> i64 {
+    parse_input(input)
+        .iter()
+        .map(|list| predict_prev(list))
+        .sum()
+}
+
+/// Extrapolates the polynomial through `sequence` to position `index`
+///
+/// The sequence's own values are at positions `0..sequence.len()`, so `sequence.len()` gives
+/// the next value and `-1` the previous one.
+///
+/// Uses the closed form of the difference tower, weighting each value by binomial
+/// coefficients, so it takes O(d) time regardless of how far away `index` is, on top of the
+/// O(n·d) it takes [`degree`] to find the degree `d` of a sequence of `n` values.
+///
+/// # Errors
+///
+/// This function will return an error if the sequence is not a polynomial (see [`degree`]) or
+/// the result does not fit in an `i128`
+pub fn predict_at(sequence: &[i64], index: i64) -> Result<i128, SequenceError> {
+    let degree = degree(sequence)?;
+
+    if let Some(&value) = usize::try_from(index).ok().and_then(|i| sequence.get(i)) {
+        return Ok(i128::from(value));
+    }
+
+    // The first `degree + 1` values are enough to pin down the polynomial
+    let points = &sequence[..=degree];
+    let len = points.len() as i64;
+
+    let result = if index < 0 {
+        // Reversing the points turns position -k into position len - 1 + k
+        extrapolate(
+            points.iter().rev(),
+            len,
+            i128::from(len - 1) - i128::from(index),
+        )
+    } else {
+        extrapolate(points.iter(), len, i128::from(index))
+    };
+
+    result.ok_or(SequenceError::Overflow)
+}
+
+/// Returns the degree of the polynomial that generates `sequence`
+///
+/// Builds the difference tower in a single buffer, stopping at the first row of zeros, so it
+/// takes O(n·d) time for a sequence of `n` values and degree `d`. A single value is a
+/// constant, so it has degree 0.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The sequence is empty
+/// * The difference tower runs out of terms before reaching a row of zeros, so the sequence
+///   can not be shown to be a polynomial
+/// * A difference does not fit in an `i128`
+pub fn degree(sequence: &[i64]) -> Result<usize, SequenceError> {
+    match sequence.len() {
+        0 => return Err(SequenceError::Empty),
+        1 => return Ok(0),
+        _ => {}
+    }
+
+    // Each row of the tower is one shorter than the last, so it overwrites the front of it
+    let mut row: Vec<i128> = sequence.iter().map(|&n| i128::from(n)).collect();
+
+    for degree in 0..sequence.len() - 1 {
+        let width = sequence.len() - 1 - degree;
+
+        for i in 0..width {
+            row[i] = row[i + 1]
+                .checked_sub(row[i])
+                .ok_or(SequenceError::Overflow)?;
+        }
+
+        if row[..width].iter().all(|n| *n == 0) {
+            return Ok(degree);
+        }
+    }
+
+    Err(SequenceError::NotPolynomial)
 }
 
-fn predict_next(list: &Vec<i64>) -> i64 {
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SequenceError {
+    Empty,
+    NotPolynomial,
+    Overflow,
+}
+
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the sequence is empty"),
+            Self::NotPolynomial => {
+                write!(
+                    f,
+                    "the differences never reach zero, so the sequence is not a polynomial"
+                )
+            }
+            Self::Overflow => write!(f, "the value does not fit in an i128"),
+        }
+    }
+}
+
+impl Error for SequenceError {}
+
+/// Predicts the value at `index` with [`predict_at`], falling back to `recursive` for lists it
+/// can not show to be a polynomial or whose prediction does not fit in an `i64`
+///
+/// The recursive predictions run the difference tower down to an empty row, which treats
+/// `1 2 4` as a polynomial that continues with 7.
+fn predict(list: &[i64], index: i64, recursive: fn(&[i64]) -> i64) -> i64 {
+    predict_at(list, index)
+        .ok()
+        .and_then(|value| i64::try_from(value).ok())
+        .unwrap_or_else(|| recursive(list))
+}
+
+/// Evaluates the polynomial through the `n` values at position `t >= n` using
+/// `sum((-1)^(n-1-i) * C(t, i) * C(t-i-1, n-1-i) * y_i)`
+fn extrapolate<'a>(values: impl Iterator<Item = &'a i64>, n: i64, t: i128) -> Option<i128> {
+    let n = i128::from(n);
+
+    // C(t, i) and C(t-i-1, n-1-i) for the current i
+    let mut choose_i = 1i128;
+    let mut choose_rest = binomial(t - 1, n - 1)?;
+    let mut total = 0i128;
+
+    for (i, &value) in (0..).zip(values) {
+        let rest = n - 1 - i;
+        let weight = choose_i.checked_mul(choose_rest)?;
+        let term = weight.checked_mul(i128::from(value))?;
+
+        total = if rest % 2 == 0 {
+            total.checked_add(term)?
+        } else {
+            total.checked_sub(term)?
+        };
+
+        choose_i = choose_i.checked_mul(t - i)? / (i + 1);
+
+        if rest > 0 {
+            choose_rest = choose_rest.checked_mul(rest)? / (t - i - 1);
+        }
+    }
+
+    Some(total)
+}
+
+fn binomial(m: i128, k: i128) -> Option<i128> {
+    let mut result = 1i128;
+
+    for j in 0..k {
+        result = result.checked_mul(m - j)? / (j + 1);
+    }
+
+    Some(result)
+}
+
+/// Predicts the value after `list` by recursing down the difference tower
+///
+/// Slower than [`predict_at`], but a reference for it.
+///
+/// # Panics
+///
+/// Panics if a difference overflows an `i64` in debug builds
+#[must_use]
+pub fn predict_next(list: &[i64]) -> i64 {
     if list.iter().all(|n| *n == 0) {
         return 0;
     }
@@ -27,7 +219,14 @@ fn predict_next(list: &Vec<i64>) -> i64 {
     list.last().unwrap() + predict_next(&diffs)
 }
 
-fn predict_prev(list: &Vec<i64>) -> i64 {
+/// Predicts the value before `list` by recursing down the difference tower, see
+/// [`predict_next`]
+///
+/// # Panics
+///
+/// Panics if a difference overflows an `i64` in debug builds
+#[must_use]
+pub fn predict_prev(list: &[i64]) -> i64 {
     if list.iter().all(|n| *n == 0) {
         return 0;
     }
@@ -97,4 +296,49 @@ mod tests {
 
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn predict_at_test() {
+        for list in get_test_output() {
+            assert_eq!(
+                predict_at(&list, list.len() as i64),
+                Ok(i128::from(predict_next(&list)))
+            );
+            assert_eq!(predict_at(&list, -1), Ok(i128::from(predict_prev(&list))));
+        }
+
+        let squares = [1, 4, 9, 16];
+
+        assert_eq!(predict_at(&squares, 2), Ok(9));
+        assert_eq!(predict_at(&squares, 9), Ok(100));
+        assert_eq!(predict_at(&squares, -1), Ok(0));
+        assert_eq!(predict_at(&squares, -5), Ok(16));
+        assert_eq!(predict_at(&[7, 7], 1_000), Ok(7));
+        assert_eq!(
+            predict_at(&[0, 1, 8, 27, 64], i64::MAX),
+            Err(SequenceError::Overflow)
+        );
+    }
+
+    #[test]
+    fn degree_test() {
+        let degrees: Vec<_> = get_test_output().iter().map(|list| degree(list)).collect();
+
+        assert_eq!(degrees, vec![Ok(1), Ok(2), Ok(3)]);
+        assert_eq!(degree(&[5, 5]), Ok(0));
+        assert_eq!(degree(&[]), Err(SequenceError::Empty));
+        assert_eq!(degree(&[7]), Ok(0));
+        assert_eq!(
+            degree(&[1, 2, 4, 8, 16, 32, 64]),
+            Err(SequenceError::NotPolynomial)
+        );
+        assert_eq!(degree(&[i64::MIN + 1, 0, i64::MAX]), Ok(1));
+        assert_eq!(predict_at(&[1, 2, 4], 3), Err(SequenceError::NotPolynomial));
+
+        // The solvers fall back to the recursive predictions for these
+        let input = ["7".to_owned(), "1 2 4".to_owned()];
+
+        assert_eq!(part1(&input), 7 + 7);
+        assert_eq!(part2(&input), 7 + 1);
+    }
 }
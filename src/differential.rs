@@ -0,0 +1,168 @@
+use std::fmt::{self, Display};
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::generate;
+
+/// A solver under test, taking an input and returning an answer
+pub type Solver = fn(&[String]) -> i64;
+
+/// The first generated input on which two solvers gave different answers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disagreement {
+    /// Seed of the generated input, before shrinking
+    pub seed: u64,
+    /// The input after shrinking
+    pub input: Vec<String>,
+    pub reference: i64,
+    pub optimized: i64,
+}
+
+impl Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "seed {}: reference gave {} but optimized gave {} for",
+            self.seed, self.reference, self.optimized
+        )?;
+
+        for line in &self.input {
+            writeln!(f, "    {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs `reference` and `optimized` on the inputs [`generate::generate`] makes for `day` from
+/// each seed, stopping at the first one where they disagree
+///
+/// # Errors
+///
+/// This function will return the first disagreement, shrunk by [`shrink`]
+///
+/// # Panics
+///
+/// Panics if `day` has no generator, or if either solver panics on a generated input
+pub fn compare(
+    day: &str,
+    seeds: Range<u64>,
+    size: usize,
+    reference: Solver,
+    optimized: Solver,
+) -> Result<(), Disagreement> {
+    for seed in seeds {
+        let input = generate::generate(day, seed, size).expect("no generator for this day");
+
+        if reference(&input) != optimized(&input) {
+            let (input, reference, optimized) = shrink(input, reference, optimized);
+
+            return Err(Disagreement {
+                seed,
+                input,
+                reference,
+                optimized,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Drops lines from `input` one at a time for as long as the solvers still disagree, returning
+/// the smallest input found along with both answers for it
+///
+/// Dropping a line can leave an input the solvers can not handle, so panics are caught and
+/// count as agreeing. Their messages are still printed, as the panic hook is shared with
+/// everything else running in the process.
+///
+/// # Panics
+///
+/// Panics if the solvers agree on `input` to begin with
+#[must_use]
+pub fn shrink(
+    mut input: Vec<String>,
+    reference: Solver,
+    optimized: Solver,
+) -> (Vec<String>, i64, i64) {
+    let run = |input: &[String]| {
+        panic::catch_unwind(AssertUnwindSafe(|| (reference(input), optimized(input))))
+            .ok()
+            .filter(|(a, b)| a != b)
+    };
+
+    let (mut expected, mut found) = run(&input).expect("the solvers agree on this input");
+
+    let mut index = 0;
+
+    while index < input.len() {
+        let mut candidate = input.clone();
+        candidate.remove(index);
+
+        if let Some((a, b)) = run(&candidate) {
+            // Try the same index again, as it now holds the next line
+            (input, expected, found) = (candidate, a, b);
+        } else {
+            index += 1;
+        }
+    }
+
+    (input, expected, found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day_5, day_6, day_9};
+
+    #[test]
+    fn shrink_test() {
+        // Disagrees as soon as any line contains a 7
+        let reference: Solver = |input| input.len() as i64;
+        let optimized: Solver =
+            |input| input.iter().filter(|line| !line.contains('7')).count() as i64;
+
+        let error = compare("day_9", 0..100, 10, reference, optimized).unwrap_err();
+
+        assert_eq!(error.input.len(), 1);
+        assert!(error.input[0].contains('7'));
+        assert_eq!((error.reference, error.optimized), (1, 0));
+
+        // Panicking candidates are skipped rather than kept
+        let optimized: Solver = |input| {
+            assert!(input.len() > 1);
+            input.len() as i64 + 1
+        };
+
+        let (input, _, _) = shrink(vec!["a".to_owned(), "b".to_owned()], reference, optimized);
+
+        assert_eq!(input.len(), 2);
+    }
+
+    #[test]
+    fn day_5_differential_test() {
+        compare("day_5", 0..20, 5, day_5::part2_forward, day_5::part2)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+
+    #[test]
+    fn day_6_differential_test() {
+        let reference: Solver = |input| day_6::part1_with(day_6::Method::Linear, input);
+        let optimized: Solver = |input| day_6::part1_with(day_6::Method::Quadratic, input);
+
+        compare("day_6", 0..100, 8, reference, optimized).unwrap_or_else(|error| panic!("{error}"));
+
+        let reference: Solver = |input| day_6::part2_with(day_6::Method::Linear, input);
+        let optimized: Solver = |input| day_6::part2_with(day_6::Method::Quadratic, input);
+
+        compare("day_6", 0..100, 3, reference, optimized).unwrap_or_else(|error| panic!("{error}"));
+    }
+
+    #[test]
+    fn day_9_differential_test() {
+        compare("day_9", 0..100, 10, day_9::part1_recursive, day_9::part1)
+            .unwrap_or_else(|error| panic!("{error}"));
+        compare("day_9", 0..100, 10, day_9::part2_recursive, day_9::part2)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+}
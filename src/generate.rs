@@ -0,0 +1,456 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::day_10;
+
+/// Days that [`generate`] can make inputs for
+pub const DAYS: [&str; 10] = [
+    "day_1", "day_2", "day_3", "day_4", "day_5", "day_6", "day_7", "day_8", "day_9", "day_10",
+];
+
+/// Generates a random input for `day` (as in `"day_7"`) from `seed`
+///
+/// What `size` counts depends on the day:
+///
+/// * Day 1: calibration lines
+/// * Day 2: games
+/// * Day 3: rows and columns of the schematic
+/// * Day 4: scratchcards
+/// * Day 5: ranges in each map, with the numbers spread over `100 * size` values
+/// * Day 6: races, keep this small as part 2 joins all of their digits into one race
+/// * Day 7: hands
+/// * Day 8: steps along each ghost's path
+/// * Day 9: sequences
+/// * Day 10: rows and columns of the maze, at least 5
+///
+/// Returns `None` if `day` is not one of [`DAYS`].
+#[must_use]
+pub fn generate(day: &str, seed: u64, size: usize) -> Option<Vec<String>> {
+    let mut rng = Rng::new(seed);
+
+    let input = match day {
+        "day_1" => day_1(&mut rng, size),
+        "day_2" => day_2(&mut rng, size),
+        "day_3" => day_3(&mut rng, size),
+        "day_4" => day_4(&mut rng, size),
+        "day_5" => day_5(&mut rng, size),
+        "day_6" => day_6(&mut rng, size),
+        "day_7" => day_7(&mut rng, size),
+        "day_8" => day_8(&mut rng, size),
+        "day_9" => day_9(&mut rng, size),
+        "day_10" => day_10::generate(rng.next_u64(), size.max(5), size.max(5)).input,
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+/// Small xorshift generator, so that inputs can be reproduced from a seed
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        // xorshift gets stuck at 0, so mix the seed first
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..bound`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is 0
+    pub fn below(&mut self, bound: usize) -> usize {
+        // The remainder is below `bound`, so it fits back in a usize
+        usize::try_from(self.next_u64() % u64::try_from(bound).unwrap()).unwrap()
+    }
+
+    /// Returns a number in `low..=high`
+    pub const fn between(&mut self, low: i64, high: i64) -> i64 {
+        low.wrapping_add_unsigned(self.next_u64() % (high.abs_diff(low) + 1))
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// Returns `count` different numbers from `low..=high`, in random order
+    pub fn distinct(&mut self, count: usize, low: i64, high: i64) -> Vec<i64> {
+        let mut seen = HashSet::new();
+        let mut numbers = Vec::with_capacity(count);
+
+        while numbers.len() < count {
+            let number = self.between(low, high);
+
+            if seen.insert(number) {
+                numbers.push(number);
+            }
+        }
+
+        numbers
+    }
+}
+
+const DIGITS: &[u8] = b"0123456789";
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+const DIGIT_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+fn day_1(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+
+            // Part 1 needs at least one actual digit on every line
+            let digit_at = rng.below(6);
+
+            for index in 0..6 {
+                match rng.below(3) {
+                    _ if index == digit_at => line.push(char::from(*rng.pick(&DIGITS[1..]))),
+                    0 => line.push_str(DIGIT_WORDS[rng.below(DIGIT_WORDS.len())]),
+                    1 => line.push(char::from(*rng.pick(&DIGITS[1..]))),
+                    _ => {
+                        for _ in 0..=rng.below(4) {
+                            line.push(char::from(*rng.pick(LOWERCASE)));
+                        }
+                    }
+                }
+            }
+
+            line
+        })
+        .collect()
+}
+
+fn day_2(rng: &mut Rng, size: usize) -> Vec<String> {
+    (1..=size)
+        .map(|id| {
+            let draws: Vec<String> = (0..=rng.below(5))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+
+                    // Shuffle the colors so that their order varies between draws
+                    for index in (1..colors.len()).rev() {
+                        colors.swap(index, rng.below(index + 1));
+                    }
+
+                    colors[..=rng.below(3)]
+                        .iter()
+                        .map(|color| format!("{} {color}", rng.between(1, 20)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+
+            format!("Game {id}: {}", draws.join("; "))
+        })
+        .collect()
+}
+
+fn day_3(rng: &mut Rng, size: usize) -> Vec<String> {
+    let mut rows = vec![vec!['.'; size]; size];
+
+    for row in &mut rows {
+        let mut column = 0;
+
+        while column < size {
+            match rng.below(10) {
+                0..=1 => {
+                    let digits = (1 + rng.below(3)).min(size - column);
+
+                    for cell in &mut row[column..column + digits] {
+                        *cell = char::from(*rng.pick(DIGITS));
+                    }
+
+                    // Keep a gap so that neighbouring numbers do not merge
+                    column += digits + 1;
+                }
+                2 => {
+                    row[column] = *rng.pick(&['*', '*', '#', '+', '$', '/', '=', '%', '@', '&']);
+                    column += 1;
+                }
+                _ => column += 1,
+            }
+        }
+    }
+
+    rows.into_iter().map(String::from_iter).collect()
+}
+
+fn day_4(rng: &mut Rng, size: usize) -> Vec<String> {
+    let width = size.to_string().len();
+
+    (1..=size)
+        .map(|id| {
+            let winning = rng.distinct(10, 1, 99);
+            let played = rng.distinct(25, 1, 99);
+            let join = |numbers: Vec<i64>| {
+                numbers
+                    .iter()
+                    .map(|n| format!("{n:>2}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            format!("Card {id:>width$}: {} | {}", join(winning), join(played))
+        })
+        .collect()
+}
+
+fn day_5(rng: &mut Rng, size: usize) -> Vec<String> {
+    const MAPS: [&str; 7] = [
+        "seed-to-soil",
+        "soil-to-fertilizer",
+        "fertilizer-to-water",
+        "water-to-light",
+        "light-to-temperature",
+        "temperature-to-humidity",
+        "humidity-to-location",
+    ];
+
+    let size = size.max(1);
+    let values = 100 * i64::try_from(size).unwrap();
+
+    let seeds: Vec<String> = (0..size.min(10))
+        .map(|_| {
+            let start = rng.between(0, values - 1);
+            let length = rng.between(1, values - start);
+
+            format!("{start} {length}")
+        })
+        .collect();
+
+    let mut input = vec![format!("seeds: {}", seeds.join(" "))];
+
+    // Each map moves whole slices of 0..values around, so it can be followed both ways
+    for name in MAPS {
+        let mut cuts = rng.distinct(size - 1, 1, values - 1);
+        cuts.push(0);
+        cuts.push(values);
+        cuts.sort_unstable();
+
+        let sources: Vec<(i64, i64)> = cuts
+            .windows(2)
+            .map(|pair| (pair[0], pair[1] - pair[0]))
+            .collect();
+        let mut order: Vec<usize> = (0..sources.len()).collect();
+
+        for index in (1..order.len()).rev() {
+            order.swap(index, rng.below(index + 1));
+        }
+
+        input.push(String::new());
+        input.push(format!("{name} map:"));
+
+        let mut dest = 0;
+
+        for index in order {
+            let (source, length) = sources[index];
+
+            if dest != source {
+                input.push(format!("{dest} {source} {length}"));
+            }
+
+            dest += length;
+        }
+    }
+
+    input
+}
+
+fn day_6(rng: &mut Rng, size: usize) -> Vec<String> {
+    let races: Vec<(i64, i64)> = (0..size.max(1))
+        .map(|_| {
+            let time = rng.between(7, 99);
+            let best = (time / 2) * (time - time / 2);
+
+            (time, rng.between(1, best - 1))
+        })
+        .collect();
+
+    let line = |label: &str, value: fn(&(i64, i64)) -> i64| {
+        let values = races.iter().fold(String::new(), |mut values, race| {
+            let _ = write!(values, "{:>5}", value(race));
+            values
+        });
+
+        format!("{label}:{values}")
+    };
+
+    vec![line("Time", |race| race.0), line("Distance", |race| race.1)]
+}
+
+fn day_7(rng: &mut Rng, size: usize) -> Vec<String> {
+    const CARDS: &[u8] = b"23456789TJQKA";
+
+    (0..size)
+        .map(|_| {
+            let hand: String = (0..5).map(|_| char::from(*rng.pick(CARDS))).collect();
+
+            format!("{hand} {}", rng.between(1, 1000))
+        })
+        .collect()
+}
+
+/// Builds a network where every ghost walks a fixed cycle whatever the instructions, with
+/// the `Z` node at the end of each cycle
+///
+/// The ghost starting at `AAA` ends its cycle on `ZZZ`, so both parts have an answer. Extra
+/// nodes that no ghost can reach are mixed in with random neighbours.
+fn day_8(rng: &mut Rng, size: usize) -> Vec<String> {
+    const LETTERS: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+
+    let size = size.max(1);
+    // Names count up in bijective base 24, so they never repeat and just grow longer, starting
+    // from a random two letter one
+    let mut next_id = 25 + rng.below(LETTERS.len() * LETTERS.len());
+    let mut name = |rng: &mut Rng, last: Option<u8>| {
+        let mut id = next_id;
+        let mut letters = vec![last.unwrap_or_else(|| *rng.pick(LETTERS))];
+
+        next_id += 1;
+
+        while id > 0 {
+            id -= 1;
+            letters.push(LETTERS[id % LETTERS.len()]);
+            id /= LETTERS.len();
+        }
+
+        letters
+            .iter()
+            .rev()
+            .map(|&b| char::from(b))
+            .collect::<String>()
+    };
+
+    let mut nodes = Vec::new();
+
+    for ghost in 0..=rng.below(5) {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_owned(), "ZZZ".to_owned())
+        } else {
+            (name(rng, Some(b'A')), name(rng, Some(b'Z')))
+        };
+
+        let path: Vec<String> = (1..rng.between(2, i64::try_from(size).unwrap() + 1))
+            .map(|_| name(rng, None))
+            .chain([end])
+            .collect();
+
+        nodes.push((start, path[0].clone(), path[0].clone()));
+
+        for (index, node) in path.iter().enumerate() {
+            let next = &path[(index + 1) % path.len()];
+            nodes.push((node.clone(), next.clone(), next.clone()));
+        }
+    }
+
+    let known: Vec<String> = nodes.iter().map(|(node, _, _)| node.clone()).collect();
+
+    for _ in 0..size {
+        let node = name(rng, None);
+        let left = rng.pick(&known).clone();
+        let right = rng.pick(&known).clone();
+
+        nodes.push((node, left, right));
+    }
+
+    for index in (1..nodes.len()).rev() {
+        nodes.swap(index, rng.below(index + 1));
+    }
+
+    let instructions: String = (0..=rng.below(size))
+        .map(|_| *rng.pick(&['L', 'R']))
+        .collect();
+
+    [instructions, String::new()]
+        .into_iter()
+        .chain(
+            nodes
+                .into_iter()
+                .map(|(node, left, right)| format!("{node} = ({left}, {right})")),
+        )
+        .collect()
+}
+
+/// Samples polynomials of degree at most 5, 21 values each
+fn day_9(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let coefficients: Vec<i64> = (0..=rng.below(6)).map(|_| rng.between(-9, 9)).collect();
+
+            (0..21)
+                .map(|x: i64| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |value, coefficient| value * x + coefficient)
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day_1, day_2, day_3, day_4, day_5, day_6, day_7, day_8, day_9};
+
+    #[test]
+    fn generate_test() {
+        assert_eq!(generate("day_7", 1, 20), generate("day_7", 1, 20));
+        assert_ne!(generate("day_7", 1, 20), generate("day_7", 2, 20));
+        assert_eq!(generate("day_11", 1, 20), None);
+
+        for day in DAYS {
+            assert!(generate(day, 3, 12).is_some_and(|input| !input.is_empty()));
+        }
+
+        // More nodes than there are three letter names
+        let input = generate("day_8", 1, 20_000).unwrap();
+        let names: HashSet<&str> = input[2..]
+            .iter()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(names.len(), input.len() - 2);
+    }
+
+    #[test]
+    fn solvers_accept_generated_test() {
+        for seed in 0..20 {
+            let input = |day| generate(day, seed, 12).unwrap();
+
+            let _ = day_1::part1(&input("day_1")) + day_1::part2(&input("day_1"));
+            let _ =
+                day_2::part1(&day_2::TEST_LIMIT, &input("day_2")) + day_2::part2(&input("day_2"));
+            let _ = day_3::part1(&input("day_3")) + day_3::part2(&input("day_3"));
+            let _ = day_4::part1(&input("day_4")) + day_4::part2(&input("day_4"));
+            let _ = day_5::part1(&input("day_5")) + day_5::part2(&input("day_5"));
+            let _ = day_7::part1(&input("day_7")) + day_7::part2(&input("day_7"));
+            let _ = day_8::part1(&input("day_8")) + day_8::part2(&input("day_8"));
+            let _ = day_9::part1(&input("day_9")) + day_9::part2(&input("day_9"));
+
+            assert!(day_4::parse_cards(&input("day_4")).is_ok());
+
+            // Part 2 joins every race, so keep it to a few
+            let races = generate("day_6", seed, 3).unwrap();
+
+            assert!(day_6::part1(&races) > 0);
+            assert!(day_6::part2(&races) > 0);
+        }
+    }
+}
@@ -9,4 +9,8 @@ pub mod day_7;
 pub mod day_8;
 pub mod day_9;
 
+pub mod differential;
+pub mod generate;
+
 pub mod inputs;
+pub mod render;
@@ -9,6 +9,15 @@ struct AdventDay {
 
     part1_fn: fn(&[String]) -> i64,
     part2_fn: fn(&[String]) -> i64,
+
+    /// Printed after the results when `--report` is passed
+    report_fn: Option<fn(&[String]) -> String>,
+
+    /// Printed after the results when `--render` is passed
+    render_fn: Option<fn(&[String], render::RenderStyle) -> String>,
+
+    /// Written to the given path when `--svg=<path>` is passed
+    svg_fn: Option<fn(&[String]) -> String>,
 }
 
 const IMPLEMENTED_DAYS: [AdventDay; 10] = [
@@ -17,85 +26,167 @@ const IMPLEMENTED_DAYS: [AdventDay; 10] = [
         nice_name: "Day 1",
         part1_fn: day_1::part1,
         part2_fn: day_1::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_2",
         nice_name: "Day 2",
         part1_fn: |input| day_2::part1(&day_2::TEST_LIMIT, input),
         part2_fn: day_2::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_3",
         nice_name: "Day 3",
         part1_fn: day_3::part1,
         part2_fn: day_3::part2,
+        report_fn: None,
+        render_fn: Some(|input, style| day_3::render(input, &day_3::GEAR_RULE, style)),
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_4",
         nice_name: "Day 4",
         part1_fn: day_4::part1,
         part2_fn: day_4::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_5",
         nice_name: "Day 5",
         part1_fn: day_5::part1,
         part2_fn: day_5::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_6",
         nice_name: "Day 6",
         part1_fn: day_6::part1,
         part2_fn: day_6::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_7",
         nice_name: "Day 7",
         part1_fn: day_7::part1,
         part2_fn: day_7::part2,
+        report_fn: Some(day_7::report),
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_8",
         nice_name: "Day 8",
         part1_fn: day_8::part1,
         part2_fn: day_8::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_9",
         nice_name: "Day 9",
         part1_fn: day_9::part1,
         part2_fn: day_9::part2,
+        report_fn: None,
+        render_fn: None,
+        svg_fn: None,
     },
     AdventDay {
         mod_name: "day_10",
         nice_name: "Day 10",
         part1_fn: day_10::part1,
         part2_fn: day_10::part2,
+        report_fn: None,
+        render_fn: Some(day_10::render),
+        svg_fn: Some(day_10::to_svg),
     },
 ];
 
-fn help() {
-    eprintln!("Usage: advent2023 day_<N>");
+fn help() -> ! {
+    eprintln!(
+        "Usage: advent2023 [day_<N>] [--report] [--render[=plain|ansi|unicode]] [--svg=<path>]"
+    );
+    eprintln!("       advent2023 gen day_<N> [--seed <S>] [--size <N>]");
     std::process::exit(1);
 }
 
+/// Prints a random input for a day, see [`generate::generate`]
+fn exec_gen(args: &[String]) {
+    let mut mod_name = None;
+    let mut seed = 0;
+    let mut size = 10;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                seed = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| help())
+            }
+            "--size" => {
+                size = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| help())
+            }
+            _ if mod_name.is_none() && !arg.starts_with('-') => mod_name = Some(arg.as_str()),
+            _ => help(),
+        }
+    }
+
+    let Some(input) = generate::generate(mod_name.unwrap_or_else(|| help()), seed, size) else {
+        eprintln!("No matching day!");
+        std::process::exit(1);
+    };
+
+    for line in input {
+        println!("{line}");
+    }
+}
+
 fn main() {
-    let mut args = env::args();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() > 2 {
-        help();
+    if args.first().is_some_and(|arg| arg == "gen") {
+        exec_gen(&args[1..]);
+        return;
     }
 
-    println!("* * * * * * * * * * * * * *");
-    println!("*{:^25}*", "Advent of Code 2023");
+    let mut mod_name = None;
+    let mut show_report = false;
+    let mut render_style = None;
+    let mut svg_path = None;
 
-    if args.len() == 1 {
-        for day in IMPLEMENTED_DAYS {
-            exec_day(&day);
+    for arg in args {
+        match arg.as_str() {
+            "--report" => show_report = true,
+            "--render" | "--render=plain" => render_style = Some(render::RenderStyle::Plain),
+            "--render=ansi" => render_style = Some(render::RenderStyle::Ansi),
+            "--render=unicode" => render_style = Some(render::RenderStyle::Unicode),
+            _ if arg.starts_with("--svg=") => svg_path = Some(arg["--svg=".len()..].to_owned()),
+            _ if mod_name.is_none() && !arg.starts_with('-') => mod_name = Some(arg),
+            _ => help(),
         }
-    } else {
-        let mod_name = args.nth(1).unwrap();
+    }
+
+    println!("* * * * * * * * * * * * * *");
+    println!("*{:^25}*", "Advent of Code 2023");
 
+    if let Some(mod_name) = mod_name {
         exec_day(
             IMPLEMENTED_DAYS
                 .iter()
@@ -104,11 +195,23 @@ fn main() {
                     eprintln!("No matching day!");
                     std::process::exit(1);
                 }),
+            show_report,
+            render_style,
+            svg_path.as_deref(),
         );
+    } else {
+        for day in IMPLEMENTED_DAYS {
+            exec_day(&day, show_report, render_style, svg_path.as_deref());
+        }
     };
 }
 
-fn exec_day(day: &AdventDay) {
+fn exec_day(
+    day: &AdventDay,
+    show_report: bool,
+    render_style: Option<render::RenderStyle>,
+    svg_path: Option<&str>,
+) {
     let mod_name = day.mod_name;
     let input =
         inputs::get_input(format!("data/{mod_name}.txt").as_str()).expect("Could not parse path!");
@@ -121,4 +224,18 @@ fn exec_day(day: &AdventDay) {
     println!("*   Part 1: {result1:<14}*");
     println!("*   Part 2: {result2:<14}*");
     println!("* * * * * * * * * * * * * *");
+
+    if let (true, Some(report_fn)) = (show_report, day.report_fn) {
+        println!("{}", report_fn(&input));
+    }
+
+    if let (Some(style), Some(render_fn)) = (render_style, day.render_fn) {
+        println!("{}", render_fn(&input, style));
+    }
+
+    if let (Some(path), Some(svg_fn)) = (svg_path, day.svg_fn) {
+        if let Err(err) = std::fs::write(path, svg_fn(&input)) {
+            eprintln!("Could not write {path}: {err}");
+        }
+    }
 }
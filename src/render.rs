@@ -0,0 +1,13 @@
+/// Style used by the days that can redraw their input, such as [`crate::day_3::render`] and
+/// [`crate::day_10::render`]
+///
+/// Each day decides what the styles look like for its puzzle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RenderStyle {
+    /// Plain characters only
+    Plain,
+    /// Colors the input in place using ANSI escape codes
+    Ansi,
+    /// Uses Unicode characters such as box drawing, for days that have them
+    Unicode,
+}
@@ -155,3 +155,11 @@ fn day10_part1() {
 
     assert_eq!(result, 6_768);
 }
+
+#[test]
+fn day10_part2() {
+    let input = inputs::get_input::<String>("data/day_10.txt").expect("Could not parse path!");
+    let result = day_10::part2(&input);
+
+    assert_eq!(result, 351);
+}